@@ -0,0 +1,155 @@
+// Knockout analysis: parts of an evolved brain are disabled one at a time and the genome is
+// evaluated again, the drop in survival shows how much each sensor, inner neuron, action or
+// connection matters. Every evaluation starts from the same seed, so all of them face the same
+// selection areas and differences come from the knockout alone.
+
+use crate::{
+    brain::Layer,
+    config::Config,
+    evaluate::{self, Mode},
+    genome::Gene,
+    misc,
+    scenario::Scenario,
+    wyrm::Wyrm,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Knockout {
+    Neuron(Layer, usize), // neuron's potential is held at 0
+    Gene(usize),          // connection encoded by the gene is not wired
+}
+
+impl Knockout {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Knockout::Neuron(Layer::Sensor, _) => "sensor",
+            Knockout::Neuron(Layer::Inner, _) => "inner",
+            Knockout::Neuron(Layer::Action, _) => "action",
+            Knockout::Gene(_) => "connection",
+        }
+    }
+}
+
+pub struct Effect {
+    pub knockout: Knockout,
+    pub name: String,
+    pub survival: f32,
+}
+
+pub struct Ablation {
+    pub baseline: f32, // survival of the intact genome
+    pub trials: usize,
+    pub effects: Vec<Effect>, // most important parts first
+}
+
+impl Effect {
+    pub fn loss(&self, baseline: f32) -> f32 {
+        baseline - self.survival
+    }
+}
+
+// neurons and connections that lie on a path from a sensor to an action,
+// knocking out anything else cannot change behaviour
+pub fn components(wyrm: &Wyrm) -> Vec<(Knockout, String)> {
+    let brain = wyrm.brain();
+    let effective = brain.effective();
+    let mut components = Vec::new();
+    for (i, n) in brain.nodes.iter().enumerate() {
+        if effective[i] {
+            let id = brain.nodes[..i]
+                .iter()
+                .filter(|m| m.layer == n.layer)
+                .count();
+            components.push((Knockout::Neuron(n.layer, id), n.name.clone()));
+        }
+    }
+    for e in &brain.edges {
+        if effective[e.src] && effective[e.sink] {
+            components.push((
+                Knockout::Gene(e.gene),
+                format!(
+                    "#{} {} -> {} ({:.3})",
+                    e.gene, brain.nodes[e.src].name, brain.nodes[e.sink].name, e.weight
+                ),
+            ));
+        }
+    }
+    return components;
+}
+
+// survival of the genome cloned into whole population, trials always start from `seed`
+// so that results of different knockouts or genomes can be compared
+pub fn survival(
+    config: &Config,
+    scenario: &Option<Scenario>,
+    genome: &[Gene],
+    trials: usize,
+    seed: u64,
+    knockouts: &[Knockout],
+) -> f32 {
+    misc::seed(seed);
+    let report = evaluate::evaluate(
+        config,
+        scenario.clone(),
+        genome,
+        trials,
+        Mode::Clonal,
+        knockouts,
+    );
+    return report.mean();
+}
+
+// evaluates the genome with every component knocked out in turn
+pub fn ablate(
+    config: &Config,
+    scenario: Option<Scenario>,
+    genome: &[Gene],
+    trials: usize,
+    seed: u64,
+) -> Ablation {
+    let wyrm = Wyrm::new(
+        0,
+        0,
+        0,
+        config.inner_neurons,
+        config.max_dist,
+        genome.to_vec(),
+    );
+    let survival =
+        |knockouts: &[Knockout]| survival(config, &scenario, genome, trials, seed, knockouts);
+    let mut effects: Vec<Effect> = components(&wyrm)
+        .into_iter()
+        .map(|(knockout, name)| Effect {
+            knockout,
+            name,
+            survival: survival(&[knockout]),
+        })
+        .collect();
+    effects.sort_by(|a, b| a.survival.total_cmp(&b.survival));
+    return Ablation {
+        baseline: survival(&[]),
+        trials,
+        effects,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components() {
+        // age -> move, and inner0 -> inner0 that no sensor reaches
+        let genome = vec![Gene(0x0001ffff), Gene(0x80808000)];
+        let wyrm = Wyrm::new(0, 0, 0, 2, 1, genome);
+        let knockouts: Vec<Knockout> = components(&wyrm).into_iter().map(|(k, _)| k).collect();
+        assert_eq!(
+            knockouts,
+            vec![
+                Knockout::Neuron(Layer::Sensor, 0),
+                Knockout::Neuron(Layer::Action, 1),
+                Knockout::Gene(0)
+            ]
+        );
+    }
+}
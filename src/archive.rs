@@ -0,0 +1,148 @@
+// Hall of fame: genomes with the most surviving copies in every generation are kept,
+// so good solutions aren't lost when the population drifts away from them.
+// Archive file has one JSON entry per line and is appended to as generations end.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::Write,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::genome::{self, Gene};
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Entry {
+    pub generation: u64,
+    pub genome: String, // hex genes
+    pub copies: usize,  // wyrms that carried the genome
+    pub survived: usize,
+}
+
+impl Entry {
+    // fraction of carriers that survived
+    pub fn fitness(&self) -> f32 {
+        self.survived as f32 / self.copies as f32
+    }
+}
+
+pub struct Archive {
+    pub entries: Vec<Entry>,
+    per_generation: usize,
+    file: Option<File>,
+}
+
+impl Archive {
+    // entries of `previous` archive are kept, but not written again
+    pub fn new(
+        per_generation: usize,
+        filename: Option<&str>,
+        previous: Vec<Entry>,
+    ) -> Result<Self> {
+        let file = match filename {
+            Some(f) => Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(f)
+                    .with_context(|| format!("opening {f}"))?,
+            ),
+            None => None,
+        };
+        Ok(Archive {
+            entries: previous,
+            per_generation,
+            file,
+        })
+    }
+
+    pub fn load(filename: &str) -> Result<Vec<Entry>> {
+        let text = fs::read_to_string(filename).with_context(|| format!("reading {filename}"))?;
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| format!("{filename}:{}", i + 1))
+            })
+            .collect()
+    }
+
+    // takes genomes with the most surviving copies from a generation after selection
+    pub fn record(&mut self, generation: u64, outcomes: &[(&[Gene], bool)]) -> Result<()> {
+        let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+        for (genome, survived) in outcomes {
+            let c = counts.entry(genome::to_hex(genome)).or_default();
+            c.0 += 1;
+            c.1 += *survived as usize;
+        }
+        let mut best: Vec<Entry> = counts
+            .into_iter()
+            .filter(|(_, (_, survived))| *survived > 0)
+            .map(|(genome, (copies, survived))| Entry {
+                generation,
+                genome,
+                copies,
+                survived,
+            })
+            .collect();
+        // genome breaks ties, so the archive doesn't depend on hash map order
+        best.sort_by(|a, b| b.survived.cmp(&a.survived).then(a.genome.cmp(&b.genome)));
+        best.truncate(self.per_generation);
+        if let Some(file) = &mut self.file {
+            for e in &best {
+                writeln!(file, "{}", serde_json::to_string(e)?)?;
+            }
+        }
+        self.entries.extend(best);
+        Ok(())
+    }
+
+    // distinct genomes with the most surviving copies first, as a single lucky survivor
+    // says little; fitness and later generations break ties
+    pub fn best(&self, n: usize) -> Result<Vec<Vec<Gene>>> {
+        let mut entries: Vec<&Entry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| {
+            b.survived
+                .cmp(&a.survived)
+                .then(b.fitness().total_cmp(&a.fitness()))
+                .then(b.generation.cmp(&a.generation))
+        });
+        let mut genomes: Vec<&str> = Vec::new();
+        for e in entries {
+            if genomes.len() == n {
+                break;
+            }
+            if !genomes.contains(&e.genome.as_str()) {
+                genomes.push(&e.genome);
+            }
+        }
+        return genomes.into_iter().map(genome::from_hex).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let (a, b, c) = (vec![Gene(1)], vec![Gene(2)], vec![Gene(3)]);
+        let mut archive = Archive::new(2, None, Vec::new()).unwrap();
+        let outcomes: Vec<(&[Gene], bool)> =
+            vec![(&a, true), (&a, true), (&a, false), (&b, true), (&c, false)];
+        archive.record(1, &outcomes).unwrap();
+        assert_eq!(archive.entries.len(), 2);
+        assert_eq!(archive.entries[0].genome, "00000001");
+        assert_eq!(
+            (archive.entries[0].copies, archive.entries[0].survived),
+            (3, 2)
+        );
+        assert_eq!(archive.best(1).unwrap(), vec![a.clone()]);
+
+        // same survivors, but b survived in every copy
+        archive.record(2, &[(&b, true), (&b, true)]).unwrap();
+        assert_eq!(archive.best(5).unwrap(), vec![b, a]);
+    }
+}
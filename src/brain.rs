@@ -0,0 +1,212 @@
+// Brain is a static snapshot of wyrm's neural network: all its neurons and the connections
+// encoded by the genome, with each connection remembering the gene it came from.
+// It is used to export, draw and analyze the network without touching live neurons.
+
+use dot_writer::{Attributes, DotWriter, Rank, RankDirection, Shape, Style};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Layer {
+    Sensor,
+    Inner,
+    Action,
+}
+
+#[derive(Clone)]
+pub struct Node {
+    pub name: String,
+    pub layer: Layer,
+    pub potential: f32,
+    pub fired: Option<i32>, // direction of action fired on the last tick
+}
+
+#[derive(Clone)]
+pub struct Edge {
+    pub src: usize,
+    pub sink: usize,
+    pub weight: f32,
+    pub gene: usize, // index of the gene in genome
+}
+
+#[derive(Clone)]
+pub struct Brain {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl Brain {
+    // marks neurons lying on some path from a sensor to an action,
+    // everything else cannot affect wyrm's behaviour
+    pub fn effective(&self) -> Vec<bool> {
+        let from_sensor = self.reachable(Layer::Sensor, |e| (e.src, e.sink));
+        let to_action = self.reachable(Layer::Action, |e| (e.sink, e.src));
+        from_sensor
+            .iter()
+            .zip(to_action.iter())
+            .map(|(a, b)| *a && *b)
+            .collect()
+    }
+
+    fn reachable(&self, start: Layer, dir: fn(&Edge) -> (usize, usize)) -> Vec<bool> {
+        let mut r: Vec<bool> = self.nodes.iter().map(|n| n.layer == start).collect();
+        let mut stack: Vec<usize> = (0..self.nodes.len()).filter(|i| r[*i]).collect();
+        while let Some(n) = stack.pop() {
+            for e in &self.edges {
+                let (from, to) = dir(e);
+                if from == n && !r[to] {
+                    r[to] = true;
+                    stack.push(to);
+                }
+            }
+        }
+        return r;
+    }
+
+    // drops neurons that have no path from a sensor to an action, along with their connections
+    pub fn pruned(&self) -> Brain {
+        let effective = self.effective();
+        let mut index = vec![None; self.nodes.len()];
+        let mut nodes = Vec::new();
+        for (i, n) in self.nodes.iter().enumerate() {
+            if effective[i] {
+                index[i] = Some(nodes.len());
+                nodes.push(n.clone());
+            }
+        }
+        let edges = self
+            .edges
+            .iter()
+            .filter_map(|e| match (index[e.src], index[e.sink]) {
+                (Some(src), Some(sink)) => Some(Edge {
+                    src,
+                    sink,
+                    ..e.clone()
+                }),
+                _ => None,
+            })
+            .collect();
+        Brain { nodes, edges }
+    }
+
+    pub fn to_dot(&self) -> Vec<u8> {
+        let effective = self.effective();
+        let mut dot = Vec::new();
+        {
+            let mut writer = DotWriter::from(&mut dot);
+            let mut digraph = writer.digraph();
+            digraph
+                .graph_attributes()
+                .set_rank_direction(RankDirection::LeftRight)
+                .set("nodesep", "0.3", false);
+
+            // each layer is a cluster with its own rank, so sensors are always on the left,
+            // actions on the right and inner neurons in between
+            for (layer, label, shape, fill) in [
+                (Layer::Sensor, "sensors", Shape::Rectangle, "lightblue"),
+                (Layer::Inner, "inner", Shape::Circle, "lightyellow"),
+                (Layer::Action, "actions", Shape::Mdiamond, "lightpink"),
+            ] {
+                let mut cluster = digraph.cluster();
+                cluster
+                    .graph_attributes()
+                    .set_label(label)
+                    .set_rank(Rank::Same)
+                    .set_style(Style::Dashed);
+                for (i, n) in self.nodes.iter().enumerate() {
+                    if n.layer != layer {
+                        continue;
+                    }
+                    let mut node = cluster.node_named(n.name.clone());
+                    node.set_shape(shape).set(
+                        "tooltip",
+                        &format!("potential {:.3}", n.potential),
+                        true,
+                    );
+                    if effective[i] {
+                        node.set_style(Style::Filled).set("fillcolor", fill, false);
+                    } else {
+                        // unused neurons are shown, but greyed out
+                        node.set_style(Style::Dashed)
+                            .set("fontcolor", "gray", false);
+                    }
+                }
+            }
+
+            for e in &self.edges {
+                let (src, sink) = (&self.nodes[e.src].name, &self.nodes[e.sink].name);
+                let color = if e.weight >= 0.0 { "darkgreen" } else { "red3" };
+                let mut attrs = digraph.edge(src, sink).attributes();
+                attrs
+                    .set_label(&format!("{:.2}", e.weight))
+                    .set("color", color, false)
+                    .set("fontcolor", color, false)
+                    .set_pen_width(0.5 + e.weight.abs())
+                    .set("tooltip", &format!("gene #{}", e.gene), true);
+                if e.src == e.sink {
+                    attrs.set_style(Style::Dashed);
+                }
+            }
+        }
+        return dot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, layer: Layer) -> Node {
+        Node {
+            name: String::from(name),
+            layer,
+            potential: 0.0,
+            fired: None,
+        }
+    }
+
+    fn edge(src: usize, sink: usize) -> Edge {
+        Edge {
+            src,
+            sink,
+            weight: 1.0,
+            gene: 0,
+        }
+    }
+
+    #[test]
+    fn test_prune() {
+        let brain = Brain {
+            nodes: vec![
+                node("age", Layer::Sensor),
+                node("random", Layer::Sensor),
+                node("inner0", Layer::Inner),
+                node("inner1", Layer::Inner),
+                node("move", Layer::Action),
+                node("turn", Layer::Action),
+            ],
+            edges: vec![
+                edge(0, 2),
+                edge(2, 2),
+                edge(2, 4),
+                edge(3, 3), // inner1 gets no input but its own
+                edge(3, 5),
+            ],
+        };
+        assert_eq!(
+            brain.effective(),
+            vec![true, false, true, false, true, false]
+        );
+        let pruned = brain.pruned();
+        assert_eq!(pruned.nodes.len(), 3);
+        assert_eq!(pruned.edges.len(), 3);
+
+        // inner1 gets input, but leads nowhere
+        let brain = Brain {
+            edges: vec![edge(0, 2), edge(1, 3), edge(3, 3), edge(2, 4)],
+            ..brain
+        };
+        let pruned = brain.pruned();
+        let names: Vec<&str> = pruned.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["age", "inner0", "move"]);
+        assert_eq!(pruned.edges.len(), 2);
+    }
+}
@@ -0,0 +1,381 @@
+// Native rendering of wyrm's brain, so we don't depend on external graphviz.
+// Network is laid out in three columns (sensors, inner neurons, actions), and every connection
+// is flattened into a polyline, so SVG export, SDL drawing and hit testing share the same geometry.
+
+use std::fmt::Write as _;
+
+#[cfg(feature = "sdl")]
+use anyhow::anyhow;
+use anyhow::{Context, Result};
+#[cfg(feature = "sdl")]
+use sdl2::{
+    gfx::primitives::DrawRenderer,
+    pixels::{Color, PixelFormatEnum},
+    render::{Canvas, RenderTarget},
+    surface::Surface,
+};
+
+#[cfg(feature = "sdl")]
+use crate::frame::Frame;
+use crate::{
+    brain::{Brain, Layer},
+    frame::Rgb,
+};
+
+const CHAR_W: i32 = 8; // SDL_gfx built-in font is 8x8
+const NODE_H: i32 = 24;
+const ROW_H: i32 = 36;
+const COL_GAP: i32 = 120;
+const MARGIN: i32 = 20;
+const CURVE_STEPS: usize = 16;
+
+#[derive(Clone, Copy, Default)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Rect {
+    fn right(&self) -> i32 {
+        self.x + self.w
+    }
+
+    #[cfg(feature = "sdl")]
+    fn bottom(&self) -> i32 {
+        self.y + self.h
+    }
+
+    fn center(&self) -> (i32, i32) {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+}
+
+pub struct Layout {
+    pub width: i32,
+    pub height: i32,
+    pub nodes: Vec<Rect>,
+    pub edges: Vec<Vec<(f32, f32)>>,
+}
+
+// places neurons into layered columns and routes connections between them
+pub fn layout(brain: &Brain) -> Layout {
+    let layers = [Layer::Sensor, Layer::Inner, Layer::Action];
+    let node_w = |i: usize| brain.nodes[i].name.len() as i32 * CHAR_W + 16;
+    let columns: Vec<Vec<usize>> = layers
+        .iter()
+        .map(|l| {
+            (0..brain.nodes.len())
+                .filter(|i| brain.nodes[*i].layer == *l)
+                .collect()
+        })
+        .collect();
+    let rows = columns.iter().map(|c| c.len()).max().unwrap_or(0) as i32;
+    let height = 2 * MARGIN + rows * ROW_H;
+
+    let mut nodes = vec![Rect::default(); brain.nodes.len()];
+    let mut x = MARGIN;
+    for (c, column) in columns.iter().enumerate() {
+        let mut column = column.clone();
+        if c > 0 {
+            // order neurons by mean position of their inputs to reduce crossings
+            let barycenter = |n: &usize| {
+                let ys: Vec<i32> = brain
+                    .edges
+                    .iter()
+                    .filter(|e| {
+                        e.sink == *n && e.src != *n && brain.nodes[e.src].layer != layers[c]
+                    })
+                    .map(|e| nodes[e.src].y)
+                    .collect();
+                if ys.is_empty() {
+                    i32::MAX
+                } else {
+                    ys.iter().sum::<i32>() / ys.len() as i32
+                }
+            };
+            column.sort_by_key(barycenter);
+        }
+        let w = column.iter().map(|i| node_w(*i)).max().unwrap_or(0);
+        // center shorter columns vertically
+        let top = MARGIN + (rows - column.len() as i32) * ROW_H / 2 + (ROW_H - NODE_H) / 2;
+        for (row, i) in column.iter().enumerate() {
+            nodes[*i] = Rect {
+                x,
+                y: top + row as i32 * ROW_H,
+                w,
+                h: NODE_H,
+            };
+        }
+        x += w + COL_GAP;
+    }
+    let width = x - COL_GAP + MARGIN;
+
+    let edges = brain
+        .edges
+        .iter()
+        .map(|e| {
+            let (src, sink) = (nodes[e.src], nodes[e.sink]);
+            if e.src == e.sink {
+                // loop above the node
+                let (cx, cy) = (src.x as f32 + 12.0, src.y as f32);
+                return (0..=CURVE_STEPS)
+                    .map(|i| {
+                        let a = std::f32::consts::PI * 2.0 * i as f32 / CURVE_STEPS as f32;
+                        (cx + 8.0 * a.sin(), cy - 8.0 + 8.0 * a.cos())
+                    })
+                    .collect();
+            }
+            let from = (src.right() as f32, src.center().1 as f32);
+            if brain.nodes[e.src].layer == brain.nodes[e.sink].layer {
+                // connection within the column, bulge to the right
+                let to = (sink.right() as f32, sink.center().1 as f32);
+                let bulge = 20.0 + (to.1 - from.1).abs() / 4.0;
+                return bezier(from, (from.0 + bulge, from.1), (to.0 + bulge, to.1), to);
+            }
+            let to = (sink.x as f32, sink.center().1 as f32);
+            let dx = (to.0 - from.0) / 2.0;
+            bezier(from, (from.0 + dx, from.1), (to.0 - dx, to.1), to)
+        })
+        .collect();
+
+    Layout {
+        width,
+        height,
+        nodes,
+        edges,
+    }
+}
+
+fn bezier(p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), p3: (f32, f32)) -> Vec<(f32, f32)> {
+    (0..=CURVE_STEPS)
+        .map(|i| {
+            let t = i as f32 / CURVE_STEPS as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            (
+                a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+            )
+        })
+        .collect()
+}
+
+fn node_color(layer: Layer, effective: bool) -> Rgb {
+    if !effective {
+        return [0xe0, 0xe0, 0xe0];
+    }
+    match layer {
+        Layer::Sensor => [0xad, 0xd8, 0xe6],
+        Layer::Inner => [0xff, 0xff, 0xc0],
+        Layer::Action => [0xff, 0xb6, 0xc1],
+    }
+}
+
+fn edge_color(weight: f32) -> Rgb {
+    if weight >= 0.0 {
+        [0, 0x64, 0]
+    } else {
+        [0xcd, 0, 0]
+    }
+}
+
+fn hex(c: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+pub fn to_svg(brain: &Brain, layout: &Layout) -> String {
+    let effective = brain.effective();
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="monospace" font-size="12">"#,
+        layout.width, layout.height
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+    for (e, points) in brain.edges.iter().zip(&layout.edges) {
+        let color = hex(edge_color(e.weight));
+        let path: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{x:.1},{y:.1}"))
+            .collect();
+        let (lx, ly) = points[points.len() / 2];
+        let _ = writeln!(
+            svg,
+            r#"<g><title>gene #{} ({:.3})</title><polyline points="{}" fill="none" stroke="{color}" stroke-width="{:.1}"/><text x="{lx:.1}" y="{:.1}" fill="{color}" text-anchor="middle">{:.2}</text></g>"#,
+            e.gene,
+            e.weight,
+            path.join(" "),
+            0.5 + e.weight.abs(),
+            ly - 3.0,
+            e.weight,
+        );
+    }
+    for (i, (n, r)) in brain.nodes.iter().zip(&layout.nodes).enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"<g><title>potential {:.3}</title><rect x="{}" y="{}" width="{}" height="{}" rx="4" fill="{}" stroke="{}"/><text x="{}" y="{}" text-anchor="middle" fill="{}">{}</text></g>"#,
+            n.potential,
+            r.x,
+            r.y,
+            r.w,
+            r.h,
+            hex(node_color(n.layer, effective[i])),
+            if effective[i] { "black" } else { "gray" },
+            r.center().0,
+            r.center().1 + 4,
+            if effective[i] { "black" } else { "gray" },
+            n.name,
+        );
+    }
+    svg.push_str("</svg>\n");
+    return svg;
+}
+
+// tints node colour towards green or red depending on its current potential
+#[cfg(feature = "sdl")]
+fn potential_color(base: Rgb, potential: f32) -> Color {
+    let p = potential.clamp(-1.0, 1.0).abs();
+    let target = if potential >= 0.0 {
+        [0x40, 0xd0, 0x40]
+    } else {
+        [0xf0, 0x40, 0x40]
+    };
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * p) as u8;
+    Color::RGB(
+        mix(base[0], target[0]),
+        mix(base[1], target[1]),
+        mix(base[2], target[2]),
+    )
+}
+
+// finds connection passing within few pixels from the point, if any
+#[cfg(feature = "sdl")]
+pub fn edge_at(layout: &Layout, (x, y): (i32, i32)) -> Option<usize> {
+    let (x, y) = (x as f32, y as f32);
+    let dist = |(x1, y1): (f32, f32), (x2, y2): (f32, f32)| {
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let len = dx * dx + dy * dy;
+        let t = if len > 0.0 {
+            (((x - x1) * dx + (y - y1) * dy) / len).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        ((x1 + t * dx - x).powi(2) + (y1 + t * dy - y).powi(2)).sqrt()
+    };
+    layout
+        .edges
+        .iter()
+        .enumerate()
+        .map(|(i, points)| {
+            let d = points
+                .windows(2)
+                .map(|s| dist(s[0], s[1]))
+                .fold(f32::MAX, f32::min);
+            (i, d)
+        })
+        .filter(|(_, d)| *d < 4.0)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+// draws network onto any SDL canvas, with top left corner at (x, y).
+// Neurons are tinted by their potentials, and highlighted connection is drawn on top in blue.
+#[cfg(feature = "sdl")]
+pub fn draw<T: RenderTarget>(
+    canvas: &mut Canvas<T>,
+    brain: &Brain,
+    layout: &Layout,
+    (x, y): (i32, i32),
+    highlight: Option<usize>,
+) -> Result<()> {
+    let effective = brain.effective();
+    let pt = |(px, py): (f32, f32)| ((px as i32 + x) as i16, (py as i32 + y) as i16);
+    let mut order: Vec<usize> = (0..brain.edges.len()).collect();
+    if let Some(h) = highlight {
+        order.retain(|i| *i != h);
+        order.push(h);
+    }
+    for i in order {
+        let (e, points) = (&brain.edges[i], &layout.edges[i]);
+        let (color, width) = if highlight == Some(i) {
+            (Color::BLUE, 3 + e.weight.abs() as u8)
+        } else {
+            let c = edge_color(e.weight);
+            (Color::RGB(c[0], c[1], c[2]), (1.0 + e.weight.abs()) as u8)
+        };
+        for s in points.windows(2) {
+            let ((x1, y1), (x2, y2)) = (pt(s[0]), pt(s[1]));
+            if width > 1 {
+                canvas.thick_line(x1, y1, x2, y2, width, color)
+            } else {
+                canvas.aa_line(x1, y1, x2, y2, color)
+            }
+            .map_err(|e| anyhow!(e))?;
+        }
+        let (lx, ly) = pt(points[points.len() / 2]);
+        canvas
+            .string(lx - 16, ly - 10, &format!("{:.2}", e.weight), color)
+            .map_err(|e| anyhow!(e))?;
+    }
+    for (i, (n, r)) in brain.nodes.iter().zip(&layout.nodes).enumerate() {
+        let (x1, y1) = pt((r.x as f32, r.y as f32));
+        let (x2, y2) = pt((r.right() as f32, r.bottom() as f32));
+        let text = if effective[i] {
+            Color::BLACK
+        } else {
+            Color::GRAY
+        };
+        canvas
+            .rounded_box(
+                x1,
+                y1,
+                x2,
+                y2,
+                4,
+                potential_color(node_color(n.layer, effective[i]), n.potential),
+            )
+            .and_then(|_| canvas.rounded_rectangle(x1, y1, x2, y2, 4, text))
+            .and_then(|_| canvas.string(x1 + 8, y1 + (NODE_H as i16 - 8) / 2, &n.name, text))
+            .map_err(|e| anyhow!(e))?;
+    }
+    Ok(())
+}
+
+pub fn write_svg(brain: &Brain, filename: &str) -> Result<()> {
+    let svg = to_svg(brain, &layout(brain));
+    std::fs::write(filename, svg).with_context(|| format!("writing {filename}"))
+}
+
+// renders network into offscreen SDL surface and saves it as PNG
+#[cfg(feature = "sdl")]
+pub fn write_png(brain: &Brain, filename: &str) -> Result<()> {
+    let layout = layout(brain);
+    let surface = Surface::new(
+        layout.width as u32,
+        layout.height as u32,
+        PixelFormatEnum::RGBA32,
+    )
+    .map_err(|e| anyhow!(e))?;
+    let mut canvas = surface.into_canvas().map_err(|e| anyhow!(e))?;
+    canvas.set_draw_color(Color::WHITE);
+    canvas.clear();
+    draw(&mut canvas, brain, &layout, (0, 0), None)?;
+    let surface = canvas.into_surface();
+
+    let (w, h, pitch) = (surface.width(), surface.height(), surface.pitch() as usize);
+    let pixels = surface
+        .without_lock()
+        .ok_or(anyhow!("cannot access surface pixels"))?;
+    let frame = Frame {
+        width: w,
+        height: h,
+        pixels: pixels
+            .chunks(pitch)
+            .flat_map(|row| row[..w as usize * 4].chunks(4).flat_map(|px| &px[..3]))
+            .cloned()
+            .collect(),
+    };
+    return frame.write_png(filename);
+}
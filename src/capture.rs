@@ -0,0 +1,141 @@
+// Offscreen capture of the world into PNG sequence and/or animated GIF,
+// works without a window so runs on headless servers can be reviewed afterwards.
+
+use std::fs;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    frame::{Frame, GifWriter},
+    render::{FrameRenderer, Renderer},
+    simulation::Simulation,
+    traces::Overlay,
+};
+
+// which generations to capture, e.g. "1-10,50,100-" (open range goes on forever)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Generations(Vec<(u64, Option<u64>)>);
+
+impl Generations {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut ranges = Vec::new();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let num = |s: &str| -> Result<u64> {
+                s.trim()
+                    .parse()
+                    .map_err(|_| anyhow!("bad generation '{s}' in '{spec}'"))
+            };
+            ranges.push(match part.split_once('-') {
+                Some((from, "")) => (num(from)?, None),
+                Some((from, to)) => (num(from)?, Some(num(to)?)),
+                None => (num(part)?, Some(num(part)?)),
+            });
+        }
+        if ranges.is_empty() {
+            return Err(anyhow!("no generations in '{spec}'"));
+        }
+        return Ok(Generations(ranges));
+    }
+
+    pub fn contains(&self, generation: u64) -> bool {
+        self.0
+            .iter()
+            .any(|(from, to)| generation >= *from && to.is_none_or(|to| generation <= to))
+    }
+}
+
+pub struct Capture {
+    dir: Option<String>,
+    gif: Option<GifWriter>,
+    gif_filename: Option<String>,
+    pub every_tick: bool,
+    generations: Option<Generations>, // all if not set
+    scale: u32,
+    overlay: Overlay,
+}
+
+impl Capture {
+    pub fn new(
+        dir: Option<String>,
+        gif_filename: Option<String>,
+        every_tick: bool,
+        generations: Option<Generations>,
+        scale: u32,
+        overlay: Overlay,
+    ) -> Result<Self> {
+        if let Some(dir) = &dir {
+            fs::create_dir_all(dir).with_context(|| format!("creating {dir}"))?;
+        }
+        Ok(Capture {
+            dir,
+            gif: None,
+            gif_filename,
+            every_tick,
+            generations,
+            scale,
+            overlay,
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.dir.is_some() || self.gif_filename.is_some()
+    }
+
+    pub fn wants(&self, generation: u64) -> bool {
+        self.enabled()
+            && self
+                .generations
+                .as_ref()
+                .is_none_or(|g| g.contains(generation))
+    }
+
+    // renders current state of the world, tick is None for end-of-generation frames
+    pub fn add(&mut self, sim: &Simulation, generation: u64, tick: Option<i32>) -> Result<()> {
+        let mut renderer = FrameRenderer::new(self.scale);
+        renderer.render(&sim.scene(None, self.overlay))?;
+        let frame = renderer.frame;
+        if let Some(dir) = &self.dir {
+            let filename = match tick {
+                Some(t) => format!("{dir}/gen-{generation:06}-tick-{t:04}.png"),
+                None => format!("{dir}/gen-{generation:06}.png"),
+            };
+            frame.write_png(&filename)?;
+        }
+        self.add_gif(&frame)
+    }
+
+    fn add_gif(&mut self, frame: &Frame) -> Result<()> {
+        let Some(filename) = &self.gif_filename else {
+            return Ok(());
+        };
+        if self.gif.is_none() {
+            // ticks are played at 25 fps, generations slower so each one can be seen
+            let delay = if self.every_tick { 4 } else { 20 };
+            self.gif = Some(GifWriter::create(
+                filename,
+                frame.width,
+                frame.height,
+                delay,
+            )?);
+        }
+        self.gif.as_mut().unwrap().add(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generations() {
+        let g = Generations::parse("1-3, 10,20-").unwrap();
+        assert_eq!(
+            g,
+            Generations(vec![(1, Some(3)), (10, Some(10)), (20, None)])
+        );
+        let picked: Vec<u64> = (0..25).filter(|i| g.contains(*i)).collect();
+        assert_eq!(picked, vec![1, 2, 3, 10, 20, 21, 22, 23, 24]);
+        assert!(Generations::parse("1-x").is_err());
+        assert!(Generations::parse("").is_err());
+    }
+}
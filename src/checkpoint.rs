@@ -0,0 +1,72 @@
+// Checkpoint is a snapshot of a run between generations: configuration, genomes of the
+// population about to be simulated and stats so far. It's plain JSON, with every genome
+// stored as a string of hex genes.
+
+use std::fs;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Config, genome, scenario::Scenario, simulation::Simulation, stats::GenerationStats,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: String, // of the program that wrote it
+    pub config: Config,
+    pub generation: u64, // generations completed
+    pub genomes: Vec<String>,
+    pub scenario: Option<String>, // in scenario file format, so it doesn't depend on the file
+    pub history: Vec<GenerationStats>,
+}
+
+impl Checkpoint {
+    pub fn new(sim: &Simulation, config: &Config, history: &[GenerationStats]) -> Self {
+        Checkpoint {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config: config.clone(),
+            generation: sim.state.generation,
+            genomes: sim.genomes().iter().map(|g| genome::to_hex(g)).collect(),
+            scenario: sim.scenario.as_ref().map(|s| s.to_text()),
+            history: history.to_vec(),
+        }
+    }
+
+    pub fn save(&self, filename: &str) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(filename, json).with_context(|| format!("writing {filename}"))
+    }
+
+    pub fn load(filename: &str) -> Result<Self> {
+        let json = fs::read_to_string(filename).with_context(|| format!("reading {filename}"))?;
+        return serde_json::from_str(&json).with_context(|| format!("parsing {filename}"));
+    }
+
+    pub fn genomes(&self) -> Result<Vec<Vec<genome::Gene>>> {
+        self.genomes
+            .iter()
+            .enumerate()
+            .map(|(i, g)| genome::from_hex(g).with_context(|| format!("genome #{i}")))
+            .collect()
+    }
+
+    // simulation in the same state as when checkpoint was taken
+    pub fn restore(&self) -> Result<Simulation> {
+        if self.genomes.len() != self.config.population {
+            return Err(anyhow!(
+                "checkpoint has {} genomes, but population is {}",
+                self.genomes.len(),
+                self.config.population
+            ));
+        }
+        let mut sim = Simulation::new(&self.config);
+        if let Some(text) = &self.scenario {
+            let scenario = Scenario::parse(text, self.config.size_x, self.config.size_y)
+                .context("checkpoint scenario")?;
+            sim.set_scenario(Some(scenario));
+        }
+        sim.set_genomes(self.genomes()?, self.generation);
+        return Ok(sim);
+    }
+}
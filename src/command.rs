@@ -0,0 +1,34 @@
+// Commands are what front-ends (SDL window, terminal) ask the main loop to do,
+// so it doesn't care where the input came from.
+
+#[cfg(feature = "sdl")]
+use crate::scenario::Cell;
+
+pub enum Command {
+    Quit,
+    Interrupt, // Ctrl-C caught by the front-end instead of raising SIGINT
+    #[cfg(feature = "sdl")]
+    Dump,
+    #[cfg(feature = "sdl")]
+    Viewer,
+    Lineage,
+    Reinject, // put hall of fame genomes back into population
+    Animate,
+    Pause,
+    Step(Step),
+    Faster,
+    Slower,
+    #[cfg(feature = "sdl")]
+    Select(i32, i32),
+    #[cfg(feature = "sdl")]
+    Edit,
+    // paint cells along the line between two points
+    #[cfg(feature = "sdl")]
+    Paint((i32, i32), (i32, i32), Cell),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Step {
+    Tick,
+    Generation,
+}
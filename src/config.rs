@@ -0,0 +1,91 @@
+// Everything needed to set up a simulation. It's stored in checkpoints,
+// so a run can be resumed with exactly the same parameters.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{scenario::Scenario, simulation::AreaShape};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub size_x: i32,
+    pub size_y: i32,
+    pub population: usize,
+    pub ticks_per_gen: i32,
+    pub genome_size: usize,
+    pub inner_neurons: usize,
+    pub mutation_rate: f32,
+    pub osc_period: i32,
+    pub max_dist: i32, // how far wyrms can see
+    pub scenario: Option<String>,
+    #[serde(default)] // not in checkpoints from before it could be chosen
+    pub selection_area: AreaShape,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            size_x: 128,
+            size_y: 128,
+            population: 1000,
+            ticks_per_gen: 100,
+            genome_size: 10,
+            inner_neurons: 3,
+            mutation_rate: 0.05,
+            osc_period: 5,
+            max_dist: 50,
+            scenario: None,
+            selection_area: AreaShape::Rectangles,
+        }
+    }
+}
+
+impl Config {
+    // every wyrm needs a cell of its own, otherwise placing them never ends
+    pub fn check(&self, scenario: Option<&Scenario>) -> Result<()> {
+        if self.size_x < 1 || self.size_y < 1 {
+            return Err(anyhow!(
+                "world {}x{} has no cells",
+                self.size_x,
+                self.size_y
+            ));
+        }
+        let walls = scenario.map_or(0, |s| s.walls.iter().flatten().filter(|w| **w).count());
+        let free = (self.size_x as usize * self.size_y as usize).saturating_sub(walls);
+        if free < self.population {
+            return Err(anyhow!(
+                "world {}x{} has {free} free cells, too few for population of {}",
+                self.size_x,
+                self.size_y,
+                self.population
+            ));
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let config = Config {
+            size_x: 10,
+            size_y: 10,
+            population: 100,
+            ..Config::default()
+        };
+        assert!(config.check(None).is_ok());
+        // a single wall leaves 99 cells
+        let text = format!("#.........\n{}", "..........\n".repeat(9));
+        let scenario = Scenario::parse(&text, 10, 10).unwrap();
+        assert!(config.check(Some(&scenario)).is_err());
+        assert!(Config {
+            size_x: 0,
+            ..config
+        }
+        .check(None)
+        .is_err());
+    }
+}
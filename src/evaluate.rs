@@ -0,0 +1,136 @@
+// Scores a genome without evolving it. In every trial wyrms carrying the genome live through
+// one generation in a freshly generated selection area (or a given scenario), and how many
+// of them survive shows whether the genome learned a general strategy or got lucky.
+
+use clap::ValueEnum;
+
+use crate::{
+    ablation::Knockout, config::Config, genome::Gene, misc, scenario::Scenario,
+    simulation::Simulation,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Mode {
+    Clonal, // whole population carries the genome
+    Single, // one wyrm with the genome among wyrms with random genomes
+}
+
+pub struct Report {
+    pub mode: Mode,
+    // survival rate of wyrms carrying the genome, per trial. In single mode it's 0 or 1
+    pub rates: Vec<f32>,
+    // survival rate of random wyrms in single mode, to compare with
+    pub baseline: Vec<f32>,
+}
+
+impl Report {
+    // chance of survival over all trials
+    pub fn mean(&self) -> f32 {
+        mean(&self.rates)
+    }
+
+    pub fn baseline_mean(&self) -> Option<f32> {
+        (!self.baseline.is_empty()).then(|| mean(&self.baseline))
+    }
+
+    pub fn stdev(&self) -> f32 {
+        let mean = self.mean();
+        let n = self.rates.len().max(2) as f32;
+        let var = self.rates.iter().map(|r| (r - mean).powi(2)).sum::<f32>() / (n - 1.0);
+        return var.sqrt();
+    }
+
+    pub fn min(&self) -> f32 {
+        self.rates.iter().copied().fold(f32::MAX, f32::min)
+    }
+
+    pub fn max(&self) -> f32 {
+        self.rates.iter().copied().fold(f32::MIN, f32::max)
+    }
+
+    // trials per survival rate bin, the last bin includes 100%
+    pub fn histogram(&self, bins: usize) -> Vec<usize> {
+        let mut counts = vec![0; bins];
+        for r in &self.rates {
+            counts[((r * bins as f32) as usize).min(bins - 1)] += 1;
+        }
+        return counts;
+    }
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len().max(1) as f32
+}
+
+// every trial gets a new selection area of `config.selection_area` shape unless there's scenario.
+// Knockouts apply only to wyrms carrying the genome
+pub fn evaluate(
+    config: &Config,
+    scenario: Option<Scenario>,
+    genome: &[Gene],
+    trials: usize,
+    mode: Mode,
+    knockouts: &[Knockout],
+) -> Report {
+    let mut sim = Simulation::new(config);
+    sim.set_scenario(scenario);
+    let carriers = match mode {
+        Mode::Clonal => config.population,
+        Mode::Single => 1,
+    };
+    sim.knock_out(knockouts, carriers);
+    let mut report = Report {
+        mode,
+        rates: Vec::with_capacity(trials),
+        baseline: Vec::new(),
+    };
+    for _ in 0..trials {
+        sim.create_selection_area();
+        let genomes = match mode {
+            Mode::Clonal => vec![genome.to_vec(); config.population],
+            Mode::Single => (0..config.population)
+                .map(|i| match i {
+                    0 => genome.to_vec(),
+                    _ => (0..genome.len()).map(|_| Gene(misc::random())).collect(),
+                })
+                .collect(),
+        };
+        sim.set_genomes(genomes, 0);
+        while sim.simulation_step() < config.ticks_per_gen {}
+        let survivors = sim.apply_selection();
+        match mode {
+            Mode::Clonal => report
+                .rates
+                .push(survivors as f32 / sim.population() as f32),
+            Mode::Single => {
+                // genomes keep their order, so the evaluated one is the first wyrm
+                let survived = sim.outcomes()[0].1;
+                let others = survivors - survived as usize;
+                report.rates.push(survived as u8 as f32);
+                report
+                    .baseline
+                    .push(others as f32 / (sim.population() - 1).max(1) as f32);
+            }
+        }
+    }
+    return report;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let report = Report {
+            mode: Mode::Clonal,
+            rates: vec![0.0, 0.25, 0.5, 1.0],
+            baseline: Vec::new(),
+        };
+        assert_eq!(report.mean(), 0.4375);
+        assert_eq!((report.min(), report.max()), (0.0, 1.0));
+        assert_eq!(report.histogram(4), vec![1, 1, 1, 1]);
+        assert_eq!(report.histogram(2), vec![2, 2]);
+        assert_eq!(report.baseline_mean(), None);
+    }
+}
@@ -0,0 +1,123 @@
+// Frame is a plain RGB pixel buffer, so the world can be rendered and saved without a window
+// (or SDL at all), e.g. when running on a server.
+
+use std::{fs::File, io::BufWriter};
+
+use anyhow::{anyhow, Context, Result};
+
+pub type Rgb = [u8; 3];
+
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>, // RGB, row by row
+}
+
+impl Frame {
+    pub fn new(width: u32, height: u32) -> Self {
+        Frame {
+            width,
+            height,
+            pixels: vec![0; (width * height * 3) as usize],
+        }
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, c: Rgb) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 3;
+        self.pixels[i..i + 3].copy_from_slice(&c);
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, c: Rgb) {
+        for py in y..y + h {
+            for px in x..x + w {
+                self.set(px, py, c);
+            }
+        }
+    }
+
+    pub fn write_png(&self, filename: &str) -> Result<()> {
+        let file = File::create(filename).with_context(|| format!("creating {filename}"))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .with_context(|| format!("writing {filename}"))?;
+        writer
+            .write_image_data(&self.pixels)
+            .with_context(|| format!("writing {filename}"))?;
+        Ok(())
+    }
+}
+
+// animated GIF, frames are appended as they come
+pub struct GifWriter {
+    encoder: gif::Encoder<BufWriter<File>>,
+    filename: String,
+    delay: u16, // in hundredths of a second
+}
+
+impl GifWriter {
+    pub fn create(filename: &str, width: u32, height: u32, delay: u16) -> Result<Self> {
+        let (width, height) = gif_size(filename, width, height)?;
+        let file = File::create(filename).with_context(|| format!("creating {filename}"))?;
+        let mut encoder = gif::Encoder::new(BufWriter::new(file), width, height, &[])
+            .with_context(|| format!("writing {filename}"))?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .with_context(|| format!("writing {filename}"))?;
+        Ok(GifWriter {
+            encoder,
+            filename: filename.to_string(),
+            delay,
+        })
+    }
+
+    pub fn add(&mut self, frame: &Frame) -> Result<()> {
+        // plain world has only a handful of colours and is kept exact, but overlays can add
+        // more than 256, then the encoder quantizes with NeuQuant and colours may shift slightly
+        let (width, height) = gif_size(&self.filename, frame.width, frame.height)?;
+        let mut f = gif::Frame::from_rgb_speed(width, height, &frame.pixels, 10);
+        f.delay = self.delay;
+        self.encoder
+            .write_frame(&f)
+            .with_context(|| format!("writing {}", self.filename))
+    }
+}
+
+// GIF stores width and height in 16 bits
+fn gif_size(filename: &str, width: u32, height: u32) -> Result<(u16, u16)> {
+    match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(w), Ok(h)) => Ok((w, h)),
+        _ => Err(anyhow!(
+            "{width}x{height} frame is too large for {filename}, GIF is limited to {} pixels \
+             a side, lower --capture-scale",
+            u16::MAX
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect() {
+        let mut f = Frame::new(4, 3);
+        f.fill_rect(0, 0, 4, 1, [1, 2, 3]);
+        f.fill_rect(3, 2, 5, 5, [9, 9, 9]); // clipped
+        assert_eq!(&f.pixels[0..3], &[1, 2, 3]);
+        assert_eq!(&f.pixels[(4 + 1) * 3..(4 + 1) * 3 + 3], &[0, 0, 0]); // inside
+        assert_eq!(&f.pixels[(2 * 4 + 3) * 3..], &[9, 9, 9]);
+    }
+
+    #[test]
+    fn test_gif_size() {
+        assert_eq!(gif_size("a.gif", 65535, 2).unwrap(), (65535, 2));
+        let err = gif_size("a.gif", 2, 65536).unwrap_err().to_string();
+        assert!(err.contains("--capture-scale"));
+    }
+}
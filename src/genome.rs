@@ -8,9 +8,13 @@
 // 16 bit weight is normalized as float in range  (-4, 4]
 // note: endiannes does not matter here
 
-use rand::seq::SliceRandom;
+use std::fs;
 
-#[derive(Clone)]
+use anyhow::{anyhow, Context, Result};
+
+use crate::misc;
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct Gene(pub u32);
 
 impl Gene {
@@ -28,8 +32,8 @@ impl Gene {
 
     pub fn mutate(self: &mut Self) {
         // flip from 1 to 3 random bits
-        for _ in 0..=rand::random::<i32>() % 3 {
-            self.0 = self.0 ^ (1 << rand::random::<u32>() % 32);
+        for _ in 0..=misc::random::<i32>() % 3 {
+            self.0 = self.0 ^ (1 << misc::random::<u32>() % 32);
         }
     }
 
@@ -42,13 +46,70 @@ pub fn mix_genome(a: &Vec<Gene>, b: &Vec<Gene>) -> Vec<Gene> {
     let v = [a, b];
     let mut r = Vec::with_capacity(a.len());
     let mut n: Vec<usize> = (0..a.len()).collect();
-    n.shuffle(&mut rand::thread_rng());
+    misc::shuffle(&mut n);
     for (i, idx) in n.iter().enumerate() {
         r.push(v[i % 2][*idx].clone()); // combine new genome by randomly selecting genes from a or b
     }
     return r;
 }
 
+// fraction of differing bits between two genomes
+pub fn distance(a: &[Gene], b: &[Gene]) -> f32 {
+    let bits: u32 = a.iter().zip(b).map(|(x, y)| (x.0 ^ y.0).count_ones()).sum();
+    bits as f32 / (32 * a.len().max(1)) as f32
+}
+
+// genome as text, one 8 digit hex number per gene separated by spaces
+pub fn to_hex(genome: &[Gene]) -> String {
+    genome
+        .iter()
+        .map(|g| format!("{:08x}", g.0))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn from_hex(text: &str) -> Result<Vec<Gene>> {
+    text.split_whitespace()
+        .map(|g| {
+            u32::from_str_radix(g, 16)
+                .map(Gene)
+                .map_err(|_| anyhow!("bad gene '{g}'"))
+        })
+        .collect()
+}
+
+// Genome file is hex genes with optional `;` comment lines.
+// Genes only make sense with the right number of inner neurons, so it's recorded in the header.
+pub fn save(filename: &str, genome: &[Gene], inner_neurons: usize) -> Result<()> {
+    let text = format!(
+        "; wyrmas genome, {} genes\n; inner_neurons {inner_neurons}\n{}\n",
+        genome.len(),
+        to_hex(genome)
+    );
+    fs::write(filename, text).with_context(|| format!("writing {filename}"))
+}
+
+pub fn load(filename: &str) -> Result<(Vec<Gene>, Option<usize>)> {
+    let text = fs::read_to_string(filename).with_context(|| format!("reading {filename}"))?;
+    let mut inner_neurons = None;
+    let mut genes = String::new();
+    for line in text.lines() {
+        match line.strip_prefix(';') {
+            Some(comment) => {
+                if let Some(n) = comment.trim().strip_prefix("inner_neurons ") {
+                    inner_neurons = Some(n.trim().parse().context("bad inner_neurons")?);
+                }
+            }
+            None => genes.push_str(&format!("{line} ")),
+        }
+    }
+    let genome = from_hex(&genes).with_context(|| format!("in {filename}"))?;
+    if genome.is_empty() {
+        return Err(anyhow!("{filename}: no genes"));
+    }
+    return Ok((genome, inner_neurons));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,6 +120,28 @@ mod tests {
         assert!(Gene(0).get_weight() < -3.99);
     }
 
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance(&[Gene(1), Gene(2)], &[Gene(1), Gene(2)]), 0.0);
+        assert_eq!(
+            distance(&[Gene(0), Gene(0)], &[Gene(0xffffffff), Gene(0)]),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_hex() {
+        let genome = vec![Gene(0xdeadbeef), Gene(1)];
+        assert_eq!(to_hex(&genome), "deadbeef 00000001");
+        assert_eq!(from_hex("deadbeef\n00000001 ").unwrap(), genome);
+        assert!(from_hex("deadbeef xyz").is_err());
+
+        let filename = std::env::temp_dir().join("wyrmas-genome-test.txt");
+        let filename = filename.to_str().unwrap();
+        save(filename, &genome, 4).unwrap();
+        assert_eq!(load(filename).unwrap(), (genome, Some(4)));
+    }
+
     #[test]
     fn test_diff() {
         assert_eq!(Gene(0b0011).diff(&Gene(0b0011)), 0.0);
@@ -0,0 +1,130 @@
+// Island model: several populations evolve in parallel, one thread each, and every few
+// generations some genomes migrate to neighbouring islands. Islands only meet at migration,
+// which makes a run repeatable from its seed.
+
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    checkpoint::Checkpoint, config::Config, genome::Gene, misc, scenario::Scenario,
+    simulation::Simulation, stats::GenerationStats,
+};
+
+// genomes and the island they come from
+type Migrants = (usize, Vec<Vec<Gene>>);
+
+#[derive(Clone, Copy, Debug, ValueEnum, Serialize)]
+pub enum Topology {
+    Ring, // each island sends migrants to the next one
+    Full, // each island sends migrants to all others
+}
+
+impl Topology {
+    pub fn neighbours(self, island: usize, count: usize) -> Vec<usize> {
+        match self {
+            Topology::Ring if count > 1 => vec![(island + 1) % count],
+            Topology::Ring => vec![],
+            Topology::Full => (0..count).filter(|&i| i != island).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Islands {
+    pub configs: Vec<Config>, // one per island, they differ only in scenario
+    pub migrants: usize,      // sent to each neighbour
+    pub interval: u64,        // generations between migrations
+    pub topology: Topology,
+    pub generations: u64,
+    pub seed: u64, // island i is seeded with seed + i
+    #[serde(skip)]
+    pub scenarios: Vec<Option<Scenario>>,
+}
+
+impl Islands {
+    // `done` is called on the calling thread after every generation of every island,
+    // returns checkpoints of final populations
+    pub fn run(&self, mut done: impl FnMut(usize, &GenerationStats)) -> Vec<Checkpoint> {
+        let count = self.configs.len();
+        let (inboxes, outboxes): (Vec<Sender<Migrants>>, Vec<_>) =
+            (0..count).map(|_| mpsc::channel()).unzip();
+        let (stats_tx, stats_rx) = mpsc::channel();
+        thread::scope(|scope| {
+            let workers: Vec<_> = outboxes
+                .into_iter()
+                .enumerate()
+                .map(|(i, inbox)| {
+                    let neighbours: Vec<Sender<_>> = self
+                        .topology
+                        .neighbours(i, count)
+                        .into_iter()
+                        .map(|n| inboxes[n].clone())
+                        .collect();
+                    let stats_tx = stats_tx.clone();
+                    scope.spawn(move || self.evolve(i, inbox, neighbours, stats_tx))
+                })
+                .collect();
+            drop(stats_tx);
+            for (i, stats) in stats_rx {
+                done(i, &stats);
+            }
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        })
+    }
+
+    fn evolve(
+        &self,
+        island: usize,
+        inbox: Receiver<Migrants>,
+        neighbours: Vec<Sender<Migrants>>,
+        stats: Sender<(usize, GenerationStats)>,
+    ) -> Checkpoint {
+        misc::seed(self.seed.wrapping_add(island as u64));
+        let mut sim = Simulation::new(&self.configs[island]);
+        if self.scenarios[island].is_some() {
+            sim.set_scenario(self.scenarios[island].clone());
+        }
+        let sources = (0..self.configs.len())
+            .filter(|&i| {
+                self.topology
+                    .neighbours(i, self.configs.len())
+                    .contains(&island)
+            })
+            .count();
+        let mut history = Vec::new();
+        for generation in 1..=self.generations {
+            history.push(sim.run_generation());
+            let _ = stats.send((island, history[history.len() - 1].clone()));
+            if generation % self.interval != 0 || generation == self.generations {
+                continue;
+            }
+            // send before receiving, channels are unbounded so nobody waits on a full inbox
+            for n in &neighbours {
+                let _ = n.send((island, sim.sample_genomes(self.migrants)));
+            }
+            let mut arrived: Vec<Migrants> = inbox.iter().take(sources).collect();
+            // in the order of islands, not of arrival, to keep runs repeatable
+            arrived.sort_by_key(|(from, _)| *from);
+            sim.replace_genomes(arrived.into_iter().flat_map(|(_, g)| g).collect());
+        }
+        // Simulation can't leave its thread, checkpoint can
+        return Checkpoint::new(&sim, &self.configs[island], &history);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbours() {
+        assert_eq!(Topology::Ring.neighbours(2, 3), vec![0]);
+        assert_eq!(Topology::Ring.neighbours(0, 1), Vec::<usize>::new());
+        assert_eq!(Topology::Full.neighbours(1, 3), vec![0, 2]);
+    }
+}
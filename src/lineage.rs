@@ -0,0 +1,227 @@
+// Lineage database keeps track of who descended from whom.
+// Every wyrm gets unique ID at birth, and we record its parents and generation it was born in.
+// Children are produced by pairs of survivors, so full ancestry is a DAG, not a tree. For the
+// phylogeny we follow the first parent (the survivor that initiated breeding, see
+// Simulation::breed_survivors), and show the second parent only as an auxiliary dashed edge in dot.
+
+use std::collections::{HashMap, HashSet};
+
+use dot_writer::{Attributes, DotWriter, Style};
+
+#[derive(Clone)]
+pub struct Record {
+    pub parents: Option<(u64, u64)>,
+    pub generation: u64,
+}
+
+pub struct Lineage {
+    records: HashMap<u64, Record>,
+    next_id: u64,
+}
+
+impl Lineage {
+    pub fn new() -> Self {
+        Lineage {
+            records: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    // registers newborn wyrm and returns its ID
+    pub fn record(&mut self, parents: Option<(u64, u64)>, generation: u64) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.records.insert(
+            id,
+            Record {
+                parents,
+                generation,
+            },
+        );
+        return id;
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    // forget everyone who is not a (first parent) ancestor of living wyrmas,
+    // otherwise database will grow by the whole population every generation
+    pub fn prune(&mut self, living: &[u64]) {
+        let keep = self.ancestors(living);
+        self.records.retain(|id, _| keep.contains(id));
+    }
+
+    // set of IDs on first parent lines from given wyrmas to their founders, including themselves
+    fn ancestors(&self, ids: &[u64]) -> HashSet<u64> {
+        let mut r = HashSet::new();
+        for id in ids {
+            let mut cur = Some(*id);
+            while let Some(id) = cur {
+                if !r.insert(id) {
+                    break; // rest of the line is already there
+                }
+                cur = self.records.get(&id).and_then(|r| r.parents).map(|p| p.0);
+            }
+        }
+        return r;
+    }
+
+    // builds phylogenetic tree of given wyrmas (usually survivors of current generation).
+    // Returns list of roots and children of each node, with chains of single descendants
+    // collapsed, so only founders, branching points and leaves remain.
+    fn tree(&self, leaves: &[u64]) -> (Vec<u64>, HashMap<u64, Vec<u64>>) {
+        let nodes = self.ancestors(leaves);
+        let mut children: HashMap<u64, Vec<u64>> = HashMap::new();
+        let mut roots = Vec::new();
+        for id in &nodes {
+            match self.records.get(id).and_then(|r| r.parents) {
+                Some((p, _)) if nodes.contains(&p) => children.entry(p).or_default().push(*id),
+                _ => roots.push(*id),
+            }
+        }
+        roots.sort();
+        children.values_mut().for_each(|c| c.sort());
+
+        let leaves: HashSet<u64> = leaves.iter().cloned().collect();
+        let mut collapsed = HashMap::new();
+        let mut stack = roots.clone();
+        while let Some(id) = stack.pop() {
+            let mut kids = Vec::new();
+            for c in children.get(&id).into_iter().flatten() {
+                // skip unary nodes that are not leaves themselves
+                let mut c = *c;
+                while !leaves.contains(&c) && children.get(&c).map_or(0, |v| v.len()) == 1 {
+                    c = children[&c][0];
+                }
+                kids.push(c);
+                stack.push(c);
+            }
+            collapsed.insert(id, kids);
+        }
+        return (roots, collapsed);
+    }
+
+    fn generation(&self, id: u64) -> u64 {
+        self.records.get(&id).map_or(0, |r| r.generation)
+    }
+
+    // exports phylogeny of given wyrmas in Newick format.
+    // Branch lengths are in generations, several founders are joined under unnamed root.
+    pub fn to_newick(&self, leaves: &[u64]) -> String {
+        let (roots, children) = self.tree(leaves);
+        let mut out = String::new();
+        if roots.len() != 1 {
+            out.push('(');
+        }
+        for (i, root) in roots.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            self.write_newick(&mut out, *root, None, &children);
+        }
+        if roots.len() != 1 {
+            out.push(')');
+        }
+        out.push(';');
+        return out;
+    }
+
+    fn write_newick(
+        &self,
+        out: &mut String,
+        id: u64,
+        parent: Option<u64>,
+        children: &HashMap<u64, Vec<u64>>,
+    ) {
+        let kids = &children[&id];
+        if !kids.is_empty() {
+            out.push('(');
+            for (i, c) in kids.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                self.write_newick(out, *c, Some(id), children);
+            }
+            out.push(')');
+        }
+        out.push_str(&format!("w{id}"));
+        if let Some(p) = parent {
+            out.push_str(&format!(":{}", self.generation(id) - self.generation(p)));
+        }
+    }
+
+    // exports the same phylogeny as dot graph, with second parents shown as dashed edges
+    // where they are present in the tree
+    pub fn to_dot(&self, leaves: &[u64]) -> Vec<u8> {
+        let (roots, children) = self.tree(leaves);
+        let leaves: HashSet<u64> = leaves.iter().cloned().collect();
+        let mut dot = Vec::new();
+        {
+            let mut writer = DotWriter::from(&mut dot);
+            let mut digraph = writer.digraph();
+            let mut ids: Vec<&u64> = children.keys().collect();
+            ids.sort();
+            for id in ids {
+                let mut node = digraph.node_named(format!("w{id}"));
+                node.set_label(&format!("w{id}\\ngen {}", self.generation(*id)));
+                if leaves.contains(id) {
+                    node.set_style(Style::Bold);
+                }
+            }
+            for root in roots {
+                let mut stack = vec![root];
+                while let Some(id) = stack.pop() {
+                    for c in &children[&id] {
+                        digraph
+                            .edge(format!("w{id}"), format!("w{c}"))
+                            .attributes()
+                            .set_label(&format!("{}", self.generation(*c) - self.generation(id)));
+                        if let Some((_, p)) = self.records.get(c).and_then(|r| r.parents) {
+                            if p != id && children.contains_key(&p) {
+                                digraph
+                                    .edge(format!("w{p}"), format!("w{c}"))
+                                    .attributes()
+                                    .set_style(Style::Dashed);
+                            }
+                        }
+                        stack.push(*c);
+                    }
+                }
+            }
+        }
+        return dot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newick() {
+        let mut l = Lineage::new();
+        let a = l.record(None, 0);
+        let b = l.record(None, 0);
+        let c = l.record(Some((a, b)), 1);
+        let d = l.record(Some((c, b)), 2);
+        let e = l.record(Some((c, a)), 2);
+        let f = l.record(Some((b, a)), 1);
+        let g = l.record(Some((f, a)), 2);
+        // c is a branching point, f is collapsed into g's branch
+        assert_eq!(l.to_newick(&[d, e, g]), "(((w3:1,w4:1)w2:1)w0,(w6:2)w1);");
+        assert_eq!(l.to_newick(&[d]), "(w3:2)w0;");
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut l = Lineage::new();
+        let a = l.record(None, 0);
+        let b = l.record(None, 0);
+        let c = l.record(Some((a, b)), 1);
+        l.prune(&[c]);
+        assert_eq!(l.len(), 2);
+        assert!(!l.records.contains_key(&b));
+        assert_eq!(l.records[&c].parents, Some((a, b)));
+    }
+}
@@ -1,185 +1,1305 @@
 use std::{
-    fmt::Display,
     fs::File,
     io::Write,
-    process::{self, Stdio},
-    thread,
-    time::Instant,
+    process, thread,
+    time::{Duration, Instant},
 };
 
-use anyhow::{anyhow, Result};
-use clap::Parser;
-use sdl2::{self, event::Event, keyboard::Keycode, render::Canvas, video::Window, EventPump};
-use simulation::Simulation;
+use anyhow::{anyhow, Context, Result};
+use archive::Archive;
+use capture::{Capture, Generations};
+use checkpoint::Checkpoint;
+use clap::{Args, Parser, Subcommand};
+use command::{Command, Step};
+use config::Config;
+use evaluate::Mode;
+use islands::{Islands, Topology};
+use rundir::{ExperimentManifest, Manifest, RunDir};
+use scenario::Scenario;
+use simulation::{AreaShape, Simulation};
+use stats::GenerationStats;
+use stop::{StopCriteria, StopReason};
+use sweep::Sweep;
+use tracer::Tracer;
+use traces::Overlay;
+use tui::{init_tui, Tui};
+#[cfg(feature = "sdl")]
+use ui::init_ui;
 use wyrm::Wyrm;
 
+mod ablation;
+mod archive;
+mod brain;
+mod brainview;
+mod capture;
+mod checkpoint;
+mod command;
+mod config;
+mod evaluate;
+mod frame;
 mod genome;
+mod islands;
+mod lineage;
+mod minimize;
 mod misc;
 mod neuron;
+mod render;
+mod rundir;
+mod scenario;
 mod simulation;
+mod stats;
+mod stop;
+mod sweep;
+mod tracer;
+mod traces;
+mod tui;
+#[cfg(feature = "sdl")]
+mod ui;
 mod wyrm;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
-struct Args {
-    #[arg(short)]
-    visualize: bool,
-    #[arg(short, default_value_t = 0.05)]
+struct Cli {
+    #[command(subcommand)]
+    command: Cmd,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// evolve a new population
+    Run(RunArgs),
+    /// continue evolving population from a checkpoint
+    Resume(ResumeArgs),
+    /// watch or capture generations following a checkpoint, without writing a new one
+    Replay(ReplayArgs),
+    /// print stats and decoded genomes of a checkpoint (*.json) or a genome file
+    Inspect(InspectArgs),
+    /// draw brain encoded by a genome file
+    RenderGenome(RenderGenomeArgs),
+    /// score a genome in fresh selection areas (or a scenario) without evolving it
+    Evaluate(EvaluateArgs),
+    /// knock out each sensor, inner neuron, action and connection of a genome in turn
+    /// and report how much survival drops
+    Ablate(AblateArgs),
+    /// drop genes that cannot affect behaviour, then remove genes one by one
+    /// while survival stays within tolerance, and write the minimal genome
+    Minimize(MinimizeArgs),
+    /// evolve every combination of parameter values with several seeds in parallel
+    /// and summarize survival per generation
+    Sweep(SweepArgs),
+    /// evolve several populations in parallel, exchanging migrants every few generations
+    Islands(IslandsArgs),
+}
+
+#[derive(Args)]
+struct WorldArgs {
+    /// selection area and walls to use instead of random ones.
+    /// Press E to edit: left-drag paints selection area, shift+left-drag walls, right-drag erases;
+    /// result is saved on leaving edit mode (to scenario.txt if not set)
+    #[arg(long)]
+    scenario: Option<String>,
+    #[command(flatten)]
+    area: AreaArgs,
+}
+
+impl WorldArgs {
+    fn config(&self) -> Config {
+        Config {
+            scenario: self.scenario.clone(),
+            ..self.area.config()
+        }
+    }
+}
+
+// world without scenario, which islands take several of
+#[derive(Args)]
+struct AreaArgs {
+    /// world size in cells, there must be room for the whole population
+    #[arg(long, default_value_t = 128, value_parser = clap::value_parser!(i32).range(1..))]
+    width: i32,
+    #[arg(long, default_value_t = 128, value_parser = clap::value_parser!(i32).range(1..))]
+    height: i32,
+    /// shape of random selection areas, a new one is generated every generation
+    #[arg(long, value_enum, default_value_t = AreaShape::Rectangles)]
+    selection_area: AreaShape,
+}
+
+impl AreaArgs {
+    fn config(&self) -> Config {
+        Config {
+            size_x: self.width,
+            size_y: self.height,
+            selection_area: self.selection_area,
+            ..Config::default()
+        }
+    }
+}
+
+#[derive(Args)]
+struct RunArgs {
+    #[command(flatten)]
+    world: WorldArgs,
+    #[arg(short, long, default_value_t = 0.05)]
+    mutation_rate: f32,
+    #[arg(short, long, default_value_t = 10)]
+    genome_size: usize,
+    #[arg(short, long, default_value_t = 3)]
+    inner_neurons: usize,
+    #[command(flatten)]
+    session: SessionArgs,
+}
+
+#[derive(Args)]
+struct ResumeArgs {
+    /// checkpoint written by a previous run; --generations counts from the start of that run,
+    /// --time-limit from now
+    #[arg(value_name = "CHECKPOINT")]
+    from: String,
+    #[command(flatten)]
+    session: SessionArgs,
+}
+
+#[derive(Args)]
+struct ReplayArgs {
+    #[arg(value_name = "CHECKPOINT")]
+    from: String,
+    /// how many generations to play
+    #[arg(long, default_value_t = 1)]
+    generations: u64,
+    #[command(flatten)]
+    display: DisplayArgs,
+}
+
+#[derive(Args)]
+struct InspectArgs {
+    file: String,
+    /// how many of the most common genomes of a checkpoint to decode
+    #[arg(long, default_value_t = 5)]
+    top: usize,
+}
+
+#[derive(Args)]
+struct RenderGenomeArgs {
+    genome: String,
+    /// output file, format is given by extension: svg, dot or png (when built with SDL)
+    #[arg(short, long)]
+    output: String,
+    /// leave out neurons that cannot affect behaviour
+    #[arg(short, long)]
+    prune: bool,
+    /// overrides number of inner neurons recorded in genome file
+    #[arg(short, long)]
+    inner_neurons: Option<usize>,
+}
+
+#[derive(Args)]
+struct EvaluateArgs {
+    genome: String,
+    /// number of fresh selection areas to try
+    #[arg(long, default_value_t = 10)]
+    trials: usize,
+    /// clone the genome into whole population, or put single wyrm among random ones
+    #[arg(long, value_enum, default_value_t = Mode::Clonal)]
+    mode: Mode,
+    #[arg(long)]
+    seed: Option<u64>,
+    #[arg(short, long)]
+    inner_neurons: Option<usize>,
+    #[command(flatten)]
+    world: WorldArgs,
+}
+
+#[derive(Args)]
+struct AblateArgs {
+    genome: String,
+    /// number of fresh selection areas to try for every knockout
+    #[arg(long, default_value_t = 10)]
+    trials: usize,
+    /// every knockout is evaluated with this seed, random if not set
+    #[arg(long)]
+    seed: Option<u64>,
+    #[arg(short, long)]
+    inner_neurons: Option<usize>,
+    #[command(flatten)]
+    world: WorldArgs,
+}
+
+#[derive(Args)]
+struct MinimizeArgs {
+    genome: String,
+    /// file to write the minimal genome to
+    #[arg(short, long)]
+    output: String,
+    /// number of fresh selection areas to try for every removed gene
+    #[arg(long, default_value_t = 10)]
+    trials: usize,
+    /// how many percentage points survival may drop below the original genome's
+    #[arg(long, default_value_t = 2.0)]
+    tolerance: f32,
+    /// only drop genes that cannot affect behaviour, without evaluating anything
+    #[arg(long)]
+    effective_only: bool,
+    /// every candidate is evaluated with this seed, random if not set
+    #[arg(long)]
+    seed: Option<u64>,
+    #[arg(short, long)]
+    inner_neurons: Option<usize>,
+    #[command(flatten)]
+    world: WorldArgs,
+}
+
+#[derive(Args)]
+struct SweepArgs {
+    #[command(flatten)]
+    world: WorldArgs,
+    /// comma separated values to try
+    #[arg(short, long, value_delimiter = ',', default_value = "0.05")]
+    mutation_rate: Vec<f32>,
+    #[arg(short, long, value_delimiter = ',', default_value = "10")]
+    genome_size: Vec<usize>,
+    #[arg(short, long, value_delimiter = ',', default_value = "3")]
+    inner_neurons: Vec<usize>,
+    /// runs of every combination, seeded 1 to N so every combination sees the same seeds
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    seeds: u64,
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    generations: u64,
+    /// worker threads, number of CPUs by default
+    #[arg(long)]
+    jobs: Option<usize>,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Args)]
+struct IslandsArgs {
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u64).range(1..))]
+    islands: u64,
+    /// genomes sent to each neighbouring island, replacing random wyrms there
+    #[arg(long, default_value_t = 10)]
+    migrants: usize,
+    /// generations between migrations
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    migration_interval: u64,
+    #[arg(long, value_enum, default_value_t = Topology::Ring)]
+    topology: Topology,
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    generations: u64,
+    /// selection area and walls, can be repeated to give islands different ones
+    /// (used in turn); random selection areas when not set
+    #[arg(long)]
+    scenario: Vec<String>,
+    #[command(flatten)]
+    area: AreaArgs,
+    #[arg(short, long, default_value_t = 0.05)]
     mutation_rate: f32,
-    #[arg(short, default_value_t = 10)]
+    #[arg(short, long, default_value_t = 10)]
     genome_size: usize,
-    #[arg(short, default_value_t = 3)]
+    #[arg(short, long, default_value_t = 3)]
     inner_neurons: usize,
+    /// island i is seeded with seed + i, random by default
+    #[arg(long)]
+    seed: Option<u64>,
+    #[command(flatten)]
+    output: OutputArgs,
 }
 
-struct UI {
-    canvas: Canvas<Window>,
-    events: EventPump,
+// everything about a run that evolves population
+#[derive(Args)]
+struct SessionArgs {
+    #[command(flatten)]
+    display: DisplayArgs,
+    #[command(flatten)]
+    stop: StopArgs,
+    #[command(flatten)]
+    save: SaveArgs,
+    /// seed for random numbers, random by default. It's recorded in the manifest,
+    /// so a run can be repeated
+    #[arg(long)]
+    seed: Option<u64>,
 }
 
-fn init_ui(args: &Args, w: i32, h: i32) -> Option<UI> {
-    if !args.visualize {
-        return None;
+#[derive(Args)]
+struct DisplayArgs {
+    #[arg(short, long)]
+    visualize: bool,
+    #[arg(short, long)]
+    prune: bool,
+    /// draw every tick instead of once per generation (toggle with A).
+    /// Space pauses, period steps single tick, G steps single generation, +/- change speed
+    #[arg(short, long)]
+    animate: bool,
+    /// in animation mode, draw only every Nth tick
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(i32).range(1..))]
+    render_every: i32,
+    /// target frame rate in animation mode, 0 for unlimited
+    #[arg(long, default_value_t = 30)]
+    fps: u32,
+    /// initial zoom in pixels per cell. Mouse wheel zooms, middle or right drag pans,
+    /// F fits the world into the window
+    #[arg(long, default_value_t = 5)]
+    cell_size: i32,
+    /// write captured frames as PNG files into this directory (works without -v).
+    /// Relative paths are inside run directory
+    #[arg(long)]
+    frames: Option<String>,
+    /// write captured frames into an animated GIF
+    #[arg(long)]
+    gif: Option<String>,
+    /// capture every tick instead of the end of each generation
+    #[arg(long)]
+    capture_ticks: bool,
+    /// generations to capture, e.g. "1-10,50,100-" (all by default)
+    #[arg(long, value_parser = Generations::parse)]
+    capture_gens: Option<Generations>,
+    /// pixels per cell in captured frames
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+    capture_scale: u32,
+    /// draw fading trails or cumulative occupancy heatmap over captured frames
+    /// (T cycles the overlay in the window)
+    #[arg(long, value_enum, default_value_t = Overlay::None)]
+    capture_overlay: Overlay,
+    /// record potential of every neuron and fired actions of traced wyrms every tick
+    /// into a CSV or JSONL file, by extension. Relative paths are inside run directory
+    #[arg(long)]
+    trace: Option<String>,
+    /// comma separated indices of wyrms in population to trace
+    #[arg(long, value_delimiter = ',', default_value = "0")]
+    trace_wyrms: Vec<usize>,
+    /// generations to trace, e.g. "1-10,50,100-" (all by default)
+    #[arg(long, value_parser = Generations::parse)]
+    trace_gens: Option<Generations>,
+    /// show the world in the terminal instead of a window
+    #[arg(long, conflicts_with = "visualize")]
+    tui: bool,
+}
+
+#[derive(Args)]
+struct StopArgs {
+    /// stop after this many generations.
+    /// Exit code is 0 when run converged or completed, 2 when it hit generation limit
+    /// before converging, 3 on time limit, 130 when interrupted by a signal (Ctrl-C);
+    /// checkpoint and stats are written in every case
+    #[arg(long)]
+    generations: Option<u64>,
+    /// stop at the end of generation after this much time, e.g. 90s, 15m or 2h
+    #[arg(long, value_parser = stop::parse_duration)]
+    time_limit: Option<Duration>,
+    /// stop once survival rate is at least this many percent for --stop-window generations
+    #[arg(long)]
+    stop_survival: Option<f32>,
+    /// stop once diversity changes by at most this much over --stop-window generations
+    #[arg(long)]
+    stop_plateau: Option<f32>,
+    #[arg(long, default_value_t = 10)]
+    stop_window: usize,
+}
+
+impl StopArgs {
+    fn criteria(&self) -> StopCriteria {
+        StopCriteria {
+            generations: self.generations,
+            time_limit: self.time_limit,
+            survival: self.stop_survival.map(|pct| pct / 100.0),
+            plateau: self.stop_plateau,
+            window: self.stop_window,
+        }
     }
+}
 
-    let sdl_context = sdl2::init().unwrap();
-    let events = sdl_context.event_pump().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-    let window = video_subsystem
-        .window("wyrmas", w as u32, h as u32)
-        .position_centered()
-        .build()
-        .unwrap();
-    let mut canvas = window.into_canvas().build().unwrap();
-    canvas.clear();
-    canvas.present();
+#[derive(Args)]
+struct SaveArgs {
+    #[command(flatten)]
+    output: OutputArgs,
+    /// also write checkpoint and stats so far every N generations, not only when run stops
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    checkpoint_every: Option<u64>,
+    #[command(flatten)]
+    hall: HallArgs,
+}
 
-    return Some(UI {
-        canvas: canvas,
-        events: events,
-    });
+#[derive(Args)]
+struct HallArgs {
+    /// genomes with the most surviving copies kept from every generation
+    /// in hall of fame (archive.jsonl in run directory)
+    #[arg(long, default_value_t = 3)]
+    hall_size: usize,
+    /// start hall of fame with entries of an earlier archive.jsonl
+    #[arg(long)]
+    archive: Option<String>,
+    /// every N generations put the fittest genomes of hall of fame back into population
+    /// (H does it once)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    reinject_every: Option<u64>,
+    /// how many genomes are put back, each replacing a random wyrm
+    #[arg(long, default_value_t = 10)]
+    reinject_count: usize,
+}
+
+impl HallArgs {
+    fn archive(&self, dir: &RunDir) -> Result<Archive> {
+        let previous = match &self.archive {
+            Some(filename) => Archive::load(filename)?,
+            None => Vec::new(),
+        };
+        Archive::new(self.hall_size, Some(&dir.file("archive.jsonl")), previous)
+    }
 }
 
-fn dump_survivor(generation: u64, w: Option<&Wyrm>) -> Result<String> {
+#[derive(Args)]
+struct OutputArgs {
+    /// where run directories are created. Run directory holds manifest.json, stats.csv,
+    /// checkpoints, dumped genomes, images and captured frames
+    #[arg(long, default_value = "runs")]
+    out_dir: String,
+    /// name of run directory instead of run-<date>-<time>
+    #[arg(long)]
+    name: Option<String>,
+}
+
+impl OutputArgs {
+    fn create(&self) -> Result<RunDir> {
+        RunDir::create(&self.out_dir, self.name.as_deref())
+    }
+}
+
+fn dump_survivor(dir: &RunDir, generation: u64, w: Option<&Wyrm>, prune: bool) -> Result<String> {
     let s = w.ok_or(anyhow!("nobody survived :("))?;
-    let filename = format!("./survivor-{generation}.png");
-    let mut dot = process::Command::new("dot")
-        .args(["-Tpng", "-o", &filename])
-        .stdin(Stdio::piped())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
-    let mut stdin = dot.stdin.take().ok_or(anyhow!("failed to pipe stdin"))?;
-    stdin.write_all(&s.dump_genome())?;
-    drop(stdin);
-    dot.wait()?;
+    let brain = if prune { s.brain().pruned() } else { s.brain() };
+    let filename = dir.file(&format!("survivor-{generation}"));
+    genome::save(
+        &format!("{filename}.genome"),
+        &s.state.genome,
+        s.inner_neurons(),
+    )?;
+    File::create(format!("{filename}.dot"))
+        .and_then(|mut f| f.write_all(&s.dump_genome(prune)))
+        .with_context(|| format!("writing {filename}.dot"))?;
+    brainview::write_svg(&brain, &format!("{filename}.svg"))?;
+    #[cfg(feature = "sdl")]
+    brainview::write_png(&brain, &format!("{filename}.png"))?;
     Ok(filename)
 }
 
-fn view_survivor(generation: u64, w: Option<&Wyrm>) -> Result<String> {
-    match dump_survivor(generation, w) {
-        Ok(file) => {
-            let f = file.clone();
-            thread::spawn(move || {
-                match process::Command::new("xdg-open")
-                    .arg(file.clone())
-                    .stdin(Stdio::null())
-                    .stdout(Stdio::null())
-                    .spawn()
-                {
-                    Err(err) => println!("error opening viewer: {err}"),
-                    Ok(mut viewer) => {
-                        println!("dumping survivor to {file}");
-                        let _ = viewer.wait();
-                    }
-                }
-            });
-            Ok(f)
-        }
-        Err(err) => Err(anyhow!("error dumping survivor: {err}")),
+fn dump_lineage(dir: &RunDir, generation: u64, sim: &Simulation) -> Result<String> {
+    let survivors = sim.survivor_ids();
+    if survivors.is_empty() {
+        return Err(anyhow!("nobody survived :("));
     }
+    let filename = dir.file(&format!("lineage-{generation}"));
+    File::create(format!("{filename}.nwk"))?
+        .write_all(sim.lineage.to_newick(&survivors).as_bytes())?;
+    File::create(format!("{filename}.dot"))?.write_all(&sim.lineage.to_dot(&survivors))?;
+    Ok(filename)
 }
 
-fn main() {
-    let args = Args::parse();
-    let (size_x, size_y, cell_size, ticks_per_gen) = (128, 128, 5, 100);
-    let mut sim = Simulation::new(
-        size_x,
-        size_y,
-        5,
-        args.inner_neurons,
-        ticks_per_gen,
-        50,
-        args.genome_size,
-        1000,
-        args.mutation_rate,
-    );
-    let mut generation: u64 = 0;
-    let mut ui = init_ui(&args, size_x * cell_size, size_y * cell_size);
+// speed keys double or halve frame rate, going through unlimited (0) at the top
+fn change_fps(command: &Command, fps: u32) -> u32 {
+    match (command, fps) {
+        (Command::Faster, 0) => 0,
+        (Command::Faster, f) if f >= 240 => 0,
+        (Command::Faster, f) => f * 2,
+        (_, 0) => 240,
+        (_, f) => (f / 2).max(1),
+    }
+}
+
+// shown in the terminal front-end when it's on, printed otherwise
+fn notify(tui: &mut Option<Tui>, text: String) {
+    match tui {
+        Some(tui) => tui.message(text),
+        None => println!("{text}"),
+    }
+}
+
+fn fps_message(fps: u32) -> String {
+    match fps {
+        0 => String::from("frame rate unlimited"),
+        f => format!("target frame rate {f} fps"),
+    }
+}
+
+// writes what's needed to continue or analyze the run later
+fn save_checkpoint(
+    dir: &RunDir,
+    name: &str,
+    sim: &Simulation,
+    config: &Config,
+    history: &[GenerationStats],
+) -> Result<()> {
+    Checkpoint::new(sim, config, history).save(&dir.file(name))?;
+    stats::write_csv(history, &dir.file("stats.csv"))?;
+    Ok(())
+}
+
+// seeds random numbers, creates run directory and writes manifest into it
+fn start_session(
+    args: &SessionArgs,
+    config: &Config,
+    resumed_from: Option<&str>,
+) -> Result<RunDir> {
+    let seed = args.seed.unwrap_or_else(misc::random);
+    misc::seed(seed);
+    let dir = args.save.output.create()?;
+    dir.write_manifest(&Manifest::new(config, seed, resumed_from))?;
+    println!("writing run to {dir} (seed {seed})");
+    return Ok(dir);
+}
+
+// evolves population until it's stopped, returns exit code.
+// Everything is written into `dir`, checkpoint and stats only when there's `save`
+fn run(
+    mut sim: Simulation,
+    config: &Config,
+    mut history: Vec<GenerationStats>,
+    args: &DisplayArgs,
+    stop: &StopCriteria,
+    dir: &RunDir,
+    save: Option<&SaveArgs>,
+) -> i32 {
+    let ticks_per_gen = config.ticks_per_gen;
+    // before SDL is initialized, which only catches signals nobody else handles
+    if let Err(err) = stop::catch_signals() {
+        println!("error setting up signal handler: {err:#}");
+        return 1;
+    }
+    let mut capture = match Capture::new(
+        args.frames.as_ref().map(|f| dir.file(f)),
+        args.gif.as_ref().map(|f| dir.file(f)),
+        args.capture_ticks,
+        args.capture_gens.clone(),
+        args.capture_scale,
+        args.capture_overlay,
+    ) {
+        Ok(capture) => capture,
+        Err(err) => {
+            println!("error setting up capture: {err:#}");
+            return 1;
+        }
+    };
+    if let Some(filename) = &args.trace {
+        if let Some(i) = args.trace_wyrms.iter().find(|&&i| i >= sim.population()) {
+            println!("can't trace wyrm {i}, population is {}", sim.population());
+            return 1;
+        }
+        let (filename, wyrms) = (dir.file(filename), args.trace_wyrms.clone());
+        match Tracer::create(&filename, wyrms, args.trace_gens.clone()) {
+            Ok(tracer) => sim.tracer = Some(tracer),
+            Err(err) => {
+                println!("error setting up tracer: {err:#}");
+                return 1;
+            }
+        }
+    }
+    // replays don't add to hall of fame
+    let hall = save.map(|s| &s.hall);
+    let archive = match hall {
+        Some(hall) => hall.archive(dir),
+        None => Archive::new(0, None, Vec::new()),
+    };
+    let mut archive = match archive {
+        Ok(archive) => archive,
+        Err(err) => {
+            println!("error setting up hall of fame: {err:#}");
+            return 1;
+        }
+    };
+    let mut generation = sim.state.generation;
+    #[cfg(feature = "sdl")]
+    let mut ui = init_ui(args.visualize, config.size_x, config.size_y, args.cell_size);
+    #[cfg(not(feature = "sdl"))]
+    if args.visualize {
+        println!("built without SDL support, use --tui instead of -v");
+        return 1;
+    }
+    let mut tui = if args.tui {
+        match init_tui() {
+            Ok(tui) => Some(tui),
+            Err(err) => {
+                println!("error setting up terminal: {err:#}");
+                return 1;
+            }
+        }
+    } else {
+        None
+    };
 
     let mut tick;
+    let run_start = Instant::now();
     let mut gen_start = Instant::now();
     let mut dump = false;
-    let mut view = false;
-    'run: loop {
-        tick = sim.simulation_step();
-        if tick >= ticks_per_gen {
-            generation += 1;
-            if let Some(ref mut ui) = ui {
-                for event in ui.events.poll_iter() {
-                    match event {
-                        Event::Quit { .. }
-                        | Event::KeyDown {
-                            keycode: Some(Keycode::Escape),
-                            ..
-                        } => break 'run,
-                        Event::KeyDown {
-                            keycode: Some(Keycode::D),
-                            ..
-                        } => dump = true,
-                        Event::KeyDown {
-                            keycode: Some(Keycode::V),
-                            ..
-                        } => view = true,
-                        _ => {}
+    let mut lineage = false;
+    let mut reinject = false;
+    let mut animate = args.animate;
+    let mut fps = args.fps;
+    let mut paused = false;
+    let mut step = None;
+    #[cfg(feature = "sdl")]
+    let mut selected: Option<u64> = None;
+    let reason = 'run: loop {
+        if stop::interrupted() {
+            break 'run StopReason::Interrupted;
+        }
+        #[cfg(feature = "sdl")]
+        if let Some(ref mut ui) = ui {
+            let selected_idx = selected.and_then(|id| sim.find_wyrm(id));
+            // events are handled every tick to keep UI responsive mid-generation
+            for command in ui.poll() {
+                match command {
+                    Command::Quit => break 'run StopReason::Quit,
+                    Command::Interrupt => break 'run StopReason::Interrupted,
+                    Command::Dump => {
+                        match selected_idx {
+                            // selected wyrm is dumped right away, otherwise wait for survivors
+                            Some(i) => {
+                                match dump_survivor(dir, generation, sim.get_wyrm(i), args.prune) {
+                                    Ok(file) => {
+                                        println!("dumping selected wyrm to {file}.{{genome,dot,svg,png}}")
+                                    }
+                                    Err(err) => println!("error dumping selected wyrm: {err:#}"),
+                                }
+                            }
+                            None => dump = true,
+                        }
+                    }
+                    Command::Viewer => {
+                        ui.toggle_viewer(&sim, selected_idx.or(sim.find_survivor()).unwrap_or(0))
+                    }
+                    Command::Lineage => lineage = true,
+                    Command::Reinject if hall.is_none() => {
+                        println!("no hall of fame to put genomes back from in a replay")
+                    }
+                    Command::Reinject => reinject = true,
+                    Command::Animate => {
+                        animate = !animate;
+                        println!("animation {}", if animate { "on" } else { "off" });
+                    }
+                    Command::Pause | Command::Step(_) if ui.editing => {
+                        println!("world is frozen while editing, press E to leave edit mode")
+                    }
+                    Command::Pause => {
+                        paused = !paused;
+                        ui.set_paused(paused);
+                    }
+                    Command::Step(s) => step = Some(s),
+                    Command::Edit => {
+                        ui.editing = !ui.editing;
+                        if ui.editing {
+                            // world is frozen while being edited
+                            paused = true;
+                        } else {
+                            let scenario = sim.snapshot_scenario();
+                            let filename = match &config.scenario {
+                                Some(filename) => filename.clone(),
+                                None => dir.file("scenario.txt"),
+                            };
+                            match scenario.save(&filename) {
+                                Ok(_) => println!("saved scenario to {filename}"),
+                                Err(err) => println!("error saving scenario: {err:#}"),
+                            }
+                            sim.set_scenario(Some(scenario));
+                        }
+                        ui.set_paused(paused);
+                    }
+                    Command::Paint(from, to, cell) => {
+                        let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+                        for i in 0..=steps {
+                            sim.set_cell(
+                                from.0 + (to.0 - from.0) * i / steps,
+                                from.1 + (to.1 - from.1) * i / steps,
+                                cell,
+                            );
+                        }
+                    }
+                    Command::Select(x, y) => match sim.wyrm_at(x, y) {
+                        Some(i) => {
+                            let w = sim.get_wyrm(i).unwrap();
+                            selected = Some(w.state.id);
+                            print!("{}", w.describe());
+                            ui.show_in_viewer(i);
+                        }
+                        None => selected = None,
+                    },
+                    Command::Faster | Command::Slower => {
+                        fps = change_fps(&command, fps);
+                        println!("{}", fps_message(fps));
+                    }
+                }
+            }
+            if paused && step.is_none() {
+                ui.draw(&sim, selected.and_then(|id| sim.find_wyrm(id)), &history);
+                ui.draw_viewer(&sim);
+                ui.present(30);
+                continue;
+            }
+        }
+        if let Some(ref mut tui) = tui {
+            for command in tui.poll() {
+                match command {
+                    Command::Quit => break 'run StopReason::Quit,
+                    Command::Interrupt => break 'run StopReason::Interrupted,
+                    Command::Pause => {
+                        paused = !paused;
+                        tui.paused = paused;
+                    }
+                    Command::Step(s) => step = Some(s),
+                    Command::Lineage => lineage = true,
+                    Command::Reinject if hall.is_none() => tui.message(String::from(
+                        "no hall of fame to put genomes back from in a replay",
+                    )),
+                    Command::Reinject => reinject = true,
+                    Command::Animate => {
+                        animate = !animate;
+                        tui.message(format!("animation {}", if animate { "on" } else { "off" }));
                     }
+                    Command::Faster | Command::Slower => {
+                        fps = change_fps(&command, fps);
+                        tui.message(fps_message(fps));
+                    }
+                    // the rest only come from the window
+                    #[cfg(feature = "sdl")]
+                    _ => {}
                 }
-                sim.render(&mut ui.canvas, cell_size as i16);
-                ui.canvas.present();
             }
+            if paused && step.is_none() {
+                if tui.draw(&sim, &history, 30).is_err() {
+                    break 'run StopReason::Quit;
+                }
+                continue;
+            }
+        }
+
+        tick = sim.simulation_step();
+        let end_of_gen = tick >= ticks_per_gen;
+        let render =
+            end_of_gen || step == Some(Step::Tick) || (animate && tick % args.render_every == 0);
+        if step == Some(Step::Tick) || (end_of_gen && step == Some(Step::Generation)) {
+            step = None;
+        }
+        #[cfg(feature = "sdl")]
+        if let Some(ref mut ui) = ui {
+            // brain viewer is animated every tick
+            ui.draw_viewer(&sim);
+            if render {
+                ui.draw(&sim, selected.and_then(|id| sim.find_wyrm(id)), &history);
+                ui.present(if animate { fps } else { 0 });
+            }
+        }
+        if let Some(ref mut tui) = tui {
+            if render
+                && tui
+                    .draw(&sim, &history, if animate { fps } else { 0 })
+                    .is_err()
+            {
+                break 'run StopReason::Quit;
+            }
+        }
+        // frames show the world before selection, so it's visible who made it
+        let capture_tick = capture.every_tick.then_some(tick);
+        if (end_of_gen || capture.every_tick) && capture.wants(generation + 1) {
+            if let Err(err) = capture.add(&sim, generation + 1, capture_tick) {
+                drop(tui.take()); // restore terminal so the error can be seen
+                println!("error capturing frame: {err:#}");
+                return 1;
+            }
+        }
+        if end_of_gen {
+            generation += 1;
             let survivors = sim.apply_selection();
             if dump {
                 dump = false;
-                match dump_survivor(generation, sim.get_survivor()) {
-                    Ok(file) => println!("dumping survivor to {file}"),
-                    Err(err) => println!("error dumping survivor: {err}"),
+                match dump_survivor(dir, generation, sim.get_survivor(), args.prune) {
+                    Ok(file) => println!("dumping survivor to {file}.{{genome,dot,svg,png}}"),
+                    Err(err) => println!("error dumping survivor: {err:#}"),
                 }
             }
-            if view {
-                view = false;
-                match view_survivor(generation, sim.get_survivor()) {
-                    Ok(file) => println!("dumping survivor to {file}"),
-                    Err(err) => println!("error dumping survivor: {err}"),
+            if lineage {
+                lineage = false;
+                match dump_lineage(dir, generation, &sim) {
+                    Ok(file) => notify(
+                        &mut tui,
+                        format!(
+                            "dumping lineage to {file}.{{nwk,dot}} ({} records in database)",
+                            sim.lineage.len()
+                        ),
+                    ),
+                    Err(err) => notify(&mut tui, format!("error dumping lineage: {err:#}")),
                 }
             }
-            let selection_area = sim
-                .state
-                .selection_area
-                .iter()
-                .fold(0, |a, ys| a + ys.iter().filter(|v| **v).count());
-
-            let gen_time = Instant::now() - gen_start;
-            println!(
-                "generation {generation}: {survivors} survivors ({:.1}%), ({:.1}% of selection area taken), took {}ms ({:.1} ticks/sec, {:.1} generations/sec)",
-                100.0 * survivors as f32 / 1000.0,
-                100.0 * survivors as f32 / selection_area as f32,
-                gen_time.as_millis(),
-                ticks_per_gen as f32 / gen_time.as_secs_f32(),
-                1.0 / gen_time.as_secs_f32()
-            );
+            let stats = GenerationStats::new(&sim, survivors, gen_start.elapsed());
+            if tui.is_none() {
+                println!("{stats}");
+            }
+            history.push(stats);
+            if let Err(err) = archive.record(generation, &sim.outcomes()) {
+                notify(&mut tui, format!("error writing hall of fame: {err:#}"));
+            }
             sim.repopulate();
             sim.create_selection_area();
+            // replays have no hall of fame, nothing is put back there
+            let due = hall.filter(|h| {
+                reinject
+                    || h.reinject_every
+                        .is_some_and(|n| generation.is_multiple_of(n))
+            });
+            if let Some(hall) = due {
+                reinject = false;
+                match archive.best(hall.reinject_count) {
+                    Ok(genomes) => {
+                        let n = genomes.len();
+                        sim.replace_genomes(genomes);
+                        notify(&mut tui, format!("{n} genomes put back from hall of fame"));
+                    }
+                    Err(err) => notify(&mut tui, format!("error reading hall of fame: {err:#}")),
+                }
+            }
             gen_start = Instant::now();
+
+            let every = save.and_then(|s| s.checkpoint_every);
+            if every.is_some_and(|n| generation.is_multiple_of(n)) {
+                let name = format!("checkpoint-{generation:06}.json");
+                if let Err(err) = save_checkpoint(dir, &name, &sim, config, &history) {
+                    notify(&mut tui, format!("error writing checkpoint: {err:#}"));
+                }
+            }
+
+            if let Some(reason) = stop.check(&history, run_start.elapsed()) {
+                break 'run reason;
+            }
+        }
+    };
+
+    drop(tui.take());
+    match sim.state.tick {
+        0 => println!("stopping after generation {generation}: {reason:?}"),
+        tick => println!(
+            "stopping at tick {tick} of generation {}: {reason:?}, the generation will be started over on resume",
+            generation + 1
+        ),
+    }
+    if let (Some(tracer), Some(filename)) = (sim.tracer.take(), &args.trace) {
+        match tracer.finish() {
+            Ok(_) => println!("trace written to {}", dir.file(filename)),
+            Err(err) => println!("error: {err:#}"),
+        }
+    }
+    if save.is_some() {
+        match save_checkpoint(dir, "checkpoint.json", &sim, config, &history) {
+            Ok(_) => println!("checkpoint and stats written to {dir}"),
+            Err(err) => println!("error writing checkpoint: {err:#}"),
+        }
+        // lineage isn't kept in checkpoint, this is the only chance to get the tree
+        match dump_lineage(dir, generation, &sim) {
+            Ok(file) => println!("lineage of current population written to {file}.{{nwk,dot}}"),
+            Err(err) => println!("error writing lineage: {err:#}"),
+        }
+    }
+    return reason.exit_code(stop);
+}
+
+// scenario of the configuration, if any, after checking the world has room for population
+fn load_scenario(config: &Config) -> Result<Option<Scenario>> {
+    let scenario = config
+        .scenario
+        .as_ref()
+        .map(|f| Scenario::load(f, config.size_x, config.size_y))
+        .transpose()
+        .context("loading scenario")?;
+    config.check(scenario.as_ref())?;
+    return Ok(scenario);
+}
+
+// genome file with the number of inner neurons it was evolved with
+fn load_genome(filename: &str, inner_neurons: Option<usize>) -> Result<(Vec<genome::Gene>, usize)> {
+    let (genome, recorded) = genome::load(filename)?;
+    let inner_neurons = inner_neurons
+        .or(recorded)
+        .unwrap_or(Config::default().inner_neurons);
+    return Ok((genome, inner_neurons));
+}
+
+fn decode(genome: Vec<genome::Gene>, config: &Config) -> Wyrm {
+    Wyrm::new(0, 0, 0, config.inner_neurons, config.max_dist, genome)
+}
+
+fn start_run(args: RunArgs) -> Result<i32> {
+    let config = Config {
+        genome_size: args.genome_size,
+        inner_neurons: args.inner_neurons,
+        mutation_rate: args.mutation_rate,
+        ..args.world.config()
+    };
+    let scenario = load_scenario(&config)?;
+    let session = &args.session;
+    let dir = start_session(session, &config, None)?;
+    let mut sim = Simulation::new(&config);
+    if scenario.is_some() {
+        sim.set_scenario(scenario);
+    }
+    return Ok(run(
+        sim,
+        &config,
+        Vec::new(),
+        &session.display,
+        &session.stop.criteria(),
+        &dir,
+        Some(&session.save),
+    ));
+}
+
+fn resume(args: ResumeArgs) -> Result<i32> {
+    let checkpoint = Checkpoint::load(&args.from)?;
+    let session = &args.session;
+    let dir = start_session(session, &checkpoint.config, Some(&args.from))?;
+    let sim = checkpoint.restore()?;
+    println!(
+        "resuming {} after generation {}",
+        args.from, checkpoint.generation
+    );
+    return Ok(run(
+        sim,
+        &checkpoint.config,
+        checkpoint.history,
+        &session.display,
+        &session.stop.criteria(),
+        &dir,
+        Some(&session.save),
+    ));
+}
+
+fn replay(args: ReplayArgs) -> Result<i32> {
+    let checkpoint = Checkpoint::load(&args.from)?;
+    let sim = checkpoint.restore()?;
+    let stop = StopCriteria {
+        generations: Some(checkpoint.generation + args.generations),
+        ..Default::default()
+    };
+    return Ok(run(
+        sim,
+        &checkpoint.config,
+        checkpoint.history,
+        &args.display,
+        &stop,
+        &RunDir::current(),
+        None,
+    ));
+}
+
+fn inspect(args: InspectArgs) -> Result<()> {
+    if !args.file.ends_with(".json") {
+        let (genome, inner_neurons) = load_genome(&args.file, None)?;
+        let config = Config {
+            inner_neurons,
+            ..Config::default()
+        };
+        println!("{} genes, {inner_neurons} inner neurons", genome.len());
+        print!("{}", decode(genome, &config).describe_genome());
+        return Ok(());
+    }
+    let checkpoint = Checkpoint::load(&args.file)?;
+    println!(
+        "checkpoint written by version {} after generation {}",
+        checkpoint.version, checkpoint.generation
+    );
+    println!("{:#?}", checkpoint.config);
+    println!("recent generations:");
+    let history = &checkpoint.history;
+    for stats in &history[history.len().saturating_sub(5)..] {
+        println!("  {stats}");
+    }
+
+    // identical genomes are common once population converges
+    let mut counts: Vec<(usize, &String)> = Vec::new();
+    for g in &checkpoint.genomes {
+        match counts.iter_mut().find(|(_, other)| *other == g) {
+            Some((n, _)) => *n += 1,
+            None => counts.push((1, g)),
+        }
+    }
+    counts.sort_by_key(|c| std::cmp::Reverse(c.0));
+    println!(
+        "{} distinct genomes in population of {}",
+        counts.len(),
+        checkpoint.genomes.len()
+    );
+    for (n, g) in counts.into_iter().take(args.top) {
+        println!("{n} x {g}");
+        print!(
+            "{}",
+            decode(genome::from_hex(g)?, &checkpoint.config).describe_genome()
+        );
+    }
+    return Ok(());
+}
+
+fn render_genome(args: RenderGenomeArgs) -> Result<()> {
+    let (genome, inner_neurons) = load_genome(&args.genome, args.inner_neurons)?;
+    let config = Config {
+        inner_neurons,
+        ..Config::default()
+    };
+    let wyrm = decode(genome, &config);
+    let brain = if args.prune {
+        wyrm.brain().pruned()
+    } else {
+        wyrm.brain()
+    };
+    match args.output.rsplit('.').next() {
+        Some("svg") => brainview::write_svg(&brain, &args.output)?,
+        Some("dot") => File::create(&args.output)
+            .and_then(|mut f| f.write_all(&wyrm.dump_genome(args.prune)))
+            .with_context(|| format!("writing {}", args.output))?,
+        #[cfg(feature = "sdl")]
+        Some("png") => brainview::write_png(&brain, &args.output)?,
+        _ => return Err(anyhow!("don't know how to write {}", args.output)),
+    }
+    println!("brain written to {}", args.output);
+    return Ok(());
+}
+
+fn evaluate(args: EvaluateArgs) -> Result<()> {
+    let (genome, inner_neurons) = load_genome(&args.genome, args.inner_neurons)?;
+    let config = Config {
+        genome_size: genome.len(),
+        inner_neurons,
+        ..args.world.config()
+    };
+    let scenario = load_scenario(&config)?;
+    if let Some(seed) = args.seed {
+        misc::seed(seed);
+    }
+    let report = evaluate::evaluate(
+        &config,
+        scenario,
+        &genome,
+        args.trials.max(1),
+        args.mode,
+        &[],
+    );
+    match report.mode {
+        Mode::Clonal => {
+            println!(
+                "survival mean {:.1}%, stdev {:.1}%, min {:.1}%, max {:.1}% in {} trials",
+                100.0 * report.mean(),
+                100.0 * report.stdev(),
+                100.0 * report.min(),
+                100.0 * report.max(),
+                report.rates.len()
+            );
+            let histogram = report.histogram(10);
+            let most = histogram.iter().copied().max().unwrap_or(1).max(1);
+            for (i, n) in histogram.iter().enumerate() {
+                println!(
+                    "  {:>3}-{:<3}% {:>5} {}",
+                    i * 10,
+                    (i + 1) * 10,
+                    n,
+                    "#".repeat(n * 40 / most)
+                );
+            }
+        }
+        Mode::Single => println!(
+            "survived {:.1}% of {} trials, random wyrms {:.1}%",
+            100.0 * report.mean(),
+            report.rates.len(),
+            100.0 * report.baseline_mean().unwrap_or(0.0)
+        ),
+    }
+    return Ok(());
+}
+
+fn ablate(args: AblateArgs) -> Result<()> {
+    let (genome, inner_neurons) = load_genome(&args.genome, args.inner_neurons)?;
+    let config = Config {
+        genome_size: genome.len(),
+        inner_neurons,
+        ..args.world.config()
+    };
+    let scenario = load_scenario(&config)?;
+    let seed = args.seed.unwrap_or_else(misc::random);
+    let ablation = ablation::ablate(&config, scenario, &genome, args.trials.max(1), seed);
+    println!(
+        "intact genome survival {:.1}% in {} trials (seed {seed})",
+        100.0 * ablation.baseline,
+        ablation.trials
+    );
+    if ablation.effects.is_empty() {
+        println!("no connection leads from a sensor to an action");
+        return Ok(());
+    }
+    println!(
+        "  {:>8} {:>9}  {:<10} knocked out",
+        "drop", "survival", "kind"
+    );
+    for e in &ablation.effects {
+        println!(
+            "  {:>+7.1}% {:>8.1}%  {:<10} {}",
+            100.0 * e.loss(ablation.baseline),
+            100.0 * e.survival,
+            e.knockout.kind(),
+            e.name
+        );
+    }
+    return Ok(());
+}
+
+fn minimize(args: MinimizeArgs) -> Result<()> {
+    let (genome, inner_neurons) = load_genome(&args.genome, args.inner_neurons)?;
+    let config = Config {
+        genome_size: genome.len(),
+        inner_neurons,
+        ..args.world.config()
+    };
+    if args.effective_only {
+        let effective = decode(genome.clone(), &config).effective_genome();
+        genome::save(&args.output, &effective, inner_neurons)?;
+        println!(
+            "{} of {} genes are on a path from a sensor to an action, written to {}",
+            effective.len(),
+            genome.len(),
+            args.output
+        );
+        return Ok(());
+    }
+    let scenario = load_scenario(&config)?;
+    let seed = args.seed.unwrap_or_else(misc::random);
+    let minimized = minimize::minimize(
+        &config,
+        scenario,
+        &genome,
+        args.trials.max(1),
+        args.tolerance / 100.0,
+        seed,
+        |gene, survival, removed| {
+            println!(
+                "  {:08x} {:>6.1}% without it, {}",
+                gene.0,
+                100.0 * survival,
+                if removed { "removed" } else { "kept" }
+            )
+        },
+    );
+    genome::save(&args.output, &minimized.genome, inner_neurons)?;
+    println!(
+        "{} genes, {} effective, {} kept; survival {:.1}% -> {:.1}% in {} trials (seed {seed})",
+        minimized.original,
+        minimized.effective,
+        minimized.genome.len(),
+        100.0 * minimized.baseline,
+        100.0 * minimized.survival,
+        args.trials.max(1)
+    );
+    println!("minimal genome written to {}", args.output);
+    return Ok(());
+}
+
+fn sweep(args: SweepArgs) -> Result<()> {
+    let base = args.world.config();
+    let sweep = Sweep {
+        configs: sweep::grid(
+            &base,
+            &args.mutation_rate,
+            &args.genome_size,
+            &args.inner_neurons,
+        ),
+        seeds: (1..=args.seeds).collect(),
+        generations: args.generations,
+        scenario: load_scenario(&base)?,
+    };
+    let jobs = args
+        .jobs
+        .or(thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1);
+    let dir = args.output.create()?;
+    dir.write_manifest(&ExperimentManifest::new(&sweep))?;
+    let total = sweep.configs.len() * sweep.seeds.len();
+    println!("running {total} simulations on {jobs} threads, writing to {dir}");
+
+    let mut finished = 0;
+    let results = sweep.run(jobs, |config, seed, history| {
+        finished += 1;
+        let label = sweep::label(config);
+        let last = history.last().unwrap();
+        println!(
+            "[{finished}/{total}] {label} seed {seed}: {:.1}% survived generation {}",
+            100.0 * last.survival_rate(),
+            last.generation
+        );
+        let filename = dir.file(&format!("{label}-seed{seed}.csv"));
+        if let Err(err) = stats::write_csv(history, &filename) {
+            println!("error writing stats: {err:#}");
+        }
+    });
+    let filename = dir.file("summary.csv");
+    sweep::write_summary(&sweep, &results, &filename)?;
+    println!("summary written to {filename}");
+    return Ok(());
+}
+
+fn islands(args: IslandsArgs) -> Result<()> {
+    let base = Config {
+        genome_size: args.genome_size,
+        inner_neurons: args.inner_neurons,
+        mutation_rate: args.mutation_rate,
+        ..args.area.config()
+    };
+    let mut configs = Vec::new();
+    let mut scenarios = Vec::new();
+    for i in 0..args.islands as usize {
+        let scenario = match args.scenario.len() {
+            0 => None,
+            n => Some(args.scenario[i % n].clone()),
+        };
+        let config = Config {
+            scenario,
+            ..base.clone()
+        };
+        scenarios.push(load_scenario(&config)?);
+        configs.push(config);
+    }
+    let islands = Islands {
+        configs,
+        migrants: args.migrants.min(base.population),
+        interval: args.migration_interval,
+        topology: args.topology,
+        generations: args.generations,
+        seed: args.seed.unwrap_or_else(misc::random),
+        scenarios,
+    };
+    let dir = args.output.create()?;
+    dir.write_manifest(&ExperimentManifest::new(&islands))?;
+    println!(
+        "evolving {} islands, writing to {dir} (seed {})",
+        args.islands, islands.seed
+    );
+
+    let checkpoints = islands.run(|island, stats| println!("island {island}: {stats}"));
+    for (i, checkpoint) in checkpoints.iter().enumerate() {
+        checkpoint.save(&dir.file(&format!("island-{i}.json")))?;
+        stats::write_csv(&checkpoint.history, &dir.file(&format!("island-{i}.csv")))?;
+    }
+    println!("checkpoints and stats of every island written to {dir}");
+    return Ok(());
+}
+
+fn main() {
+    let result = match Cli::parse().command {
+        Cmd::Run(args) => start_run(args),
+        Cmd::Resume(args) => resume(args),
+        Cmd::Replay(args) => replay(args),
+        Cmd::Inspect(args) => inspect(args).map(|_| 0),
+        Cmd::RenderGenome(args) => render_genome(args).map(|_| 0),
+        Cmd::Evaluate(args) => evaluate(args).map(|_| 0),
+        Cmd::Ablate(args) => ablate(args).map(|_| 0),
+        Cmd::Minimize(args) => minimize(args).map(|_| 0),
+        Cmd::Sweep(args) => sweep(args).map(|_| 0),
+        Cmd::Islands(args) => islands(args).map(|_| 0),
+    };
+    match result {
+        Ok(code) => process::exit(code),
+        Err(err) => {
+            println!("error: {err:#}");
+            process::exit(1);
         }
     }
 }
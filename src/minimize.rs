@@ -0,0 +1,110 @@
+// Genome minimisation: genes that cannot affect behaviour are dropped first, then the rest
+// are greedily removed one at a time as long as survival stays within a tolerance of the
+// original genome. Every candidate is evaluated from the same seed, like in ablation.
+
+use crate::{ablation, config::Config, genome::Gene, scenario::Scenario, wyrm::Wyrm};
+
+pub struct Minimized {
+    pub genome: Vec<Gene>,
+    pub original: usize,  // genes in the original genome
+    pub effective: usize, // genes on a path from a sensor to an action
+    pub baseline: f32,    // survival of the original genome
+    pub survival: f32,    // survival of the minimal genome
+}
+
+fn effective_genome(config: &Config, genome: Vec<Gene>) -> Vec<Gene> {
+    Wyrm::new(0, 0, 0, config.inner_neurons, config.max_dist, genome).effective_genome()
+}
+
+// `tried` is called with every gene tried, survival without it and whether it was removed
+pub fn minimize(
+    config: &Config,
+    scenario: Option<Scenario>,
+    genome: &[Gene],
+    trials: usize,
+    tolerance: f32,
+    seed: u64,
+    tried: impl FnMut(&Gene, f32, bool),
+) -> Minimized {
+    let survival =
+        |genome: &[Gene]| ablation::survival(config, &scenario, genome, trials, seed, &[]);
+    let baseline = survival(genome);
+    let effective = effective_genome(config, genome.to_vec());
+    let minimal = reduce(effective.clone(), baseline - tolerance, survival, tried);
+    // removals may leave connections that lead nowhere
+    let minimal = effective_genome(config, minimal);
+    return Minimized {
+        survival: survival(&minimal),
+        genome: minimal,
+        original: genome.len(),
+        effective: effective.len(),
+        baseline,
+    };
+}
+
+// removes genes one at a time, each removal is kept if survival stays at least `threshold`
+fn reduce(
+    mut genome: Vec<Gene>,
+    threshold: f32,
+    survival: impl Fn(&[Gene]) -> f32,
+    mut tried: impl FnMut(&Gene, f32, bool),
+) -> Vec<Gene> {
+    let mut i = 0;
+    while i < genome.len() {
+        let mut candidate = genome.clone();
+        let gene = candidate.remove(i);
+        let s = survival(&candidate);
+        let removed = s >= threshold;
+        tried(&gene, s, removed);
+        if removed {
+            genome = candidate;
+        } else {
+            i += 1;
+        }
+    }
+    return genome;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_genome() {
+        let config = Config {
+            inner_neurons: 3,
+            ..Config::default()
+        };
+        let genome = vec![
+            Gene(0x0001ffff), // age -> move
+            Gene(0x81818000), // inner1 -> inner1, no input
+            Gene(0x0080ffff), // age -> inner0
+            Gene(0x8001ffff), // inner0 -> move
+            Gene(0x0082ffff), // age -> inner2, leads nowhere
+        ];
+        assert_eq!(
+            effective_genome(&config, genome),
+            vec![Gene(0x0001ffff), Gene(0x0080ffff), Gene(0x8001ffff)]
+        );
+    }
+
+    #[test]
+    fn test_reduce() {
+        // genes 1 and 2 each add to survival, 3 and 4 do nothing
+        let survival = |genome: &[Gene]| {
+            0.2 + 0.5 * genome.contains(&Gene(1)) as u8 as f32
+                + 0.05 * genome.contains(&Gene(2)) as u8 as f32
+        };
+        let genome = vec![Gene(3), Gene(1), Gene(4), Gene(2)];
+        let mut log = Vec::new();
+        let minimal = reduce(genome.clone(), 0.75 - 0.1, survival, |g, _, removed| {
+            log.push((g.0, removed))
+        });
+        assert_eq!(minimal, vec![Gene(1)]);
+        assert_eq!(log, vec![(3, true), (1, false), (4, true), (2, true)]);
+
+        // tolerance too small to lose gene 2
+        let minimal = reduce(genome, 0.75 - 0.01, survival, |_, _, _| {});
+        assert_eq!(minimal, vec![Gene(1), Gene(2)]);
+    }
+}
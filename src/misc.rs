@@ -1,3 +1,12 @@
+use std::cell::RefCell;
+
+use rand::{
+    distributions::{Distribution, Standard},
+    rngs::StdRng,
+    seq::SliceRandom,
+    Rng, SeedableRng,
+};
+
 #[derive(Clone)]
 pub struct Dir(pub i32, pub i32);
 
@@ -12,7 +21,21 @@ pub static DIRECTIONS: &'static [Dir] = &[
     Dir(1, 1),   // SE
 ];
 
+#[cfg(feature = "sdl")]
+pub static DIRECTION_NAMES: &[&str] = &["E", "NE", "N", "NW", "W", "SW", "S", "SE"];
+
 impl Dir {
+    #[cfg(feature = "sdl")]
+    pub fn name(&self) -> &'static str {
+        match DIRECTIONS
+            .iter()
+            .position(|x| self.0 == x.0 && self.1 == x.1)
+        {
+            None => "?",
+            Some(i) => DIRECTION_NAMES[i],
+        }
+    }
+
     pub fn normalize(self: &Self) -> f32 {
         match DIRECTIONS
             .iter()
@@ -24,3 +47,24 @@ impl Dir {
         }
     }
 }
+
+// All randomness goes through a per-thread generator, so a run can be repeated from its seed.
+// Every thread starts with a random seed until `seed` is called.
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+pub fn random<T>() -> T
+where
+    Standard: Distribution<T>,
+{
+    RNG.with(|rng| rng.borrow_mut().gen())
+}
+
+pub fn shuffle<T>(items: &mut [T]) {
+    RNG.with(|rng| items.shuffle(&mut *rng.borrow_mut()));
+}
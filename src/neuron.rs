@@ -1,7 +1,7 @@
 use std::{cell::RefCell, rc::Rc};
 
 use crate::{
-    misc::{Dir, DIRECTIONS},
+    misc::{self, Dir, DIRECTIONS},
     simulation::{self, SimulationState},
     wyrm::{self, WyrmState},
 };
@@ -46,6 +46,8 @@ pub static INNER_NAME: &'static str = "inner";
 pub struct Neuron {
     pub name: String,
     pub potential: f32,
+    pub disabled: bool, // knocked out neuron stays silent: senses nothing, fires no action
+    pub fired: Option<i32>, // direction of the action fired on the last tick
     activate: ActivationFn,
     inputs: Vec<Link>,
 }
@@ -60,6 +62,8 @@ impl Neuron {
         Neuron {
             name,
             potential: 0.0,
+            disabled: false,
+            fired: None,
             activate: activate,
             inputs: Vec::with_capacity(1),
         }
@@ -71,23 +75,17 @@ impl Neuron {
         });
     }
 
-    pub fn get_inputs(&self) -> Vec<(String, f32)> {
-        let mut inputs = Vec::with_capacity(self.inputs.len());
-        for link in &self.inputs {
-            inputs.push(match &link.source {
-                None => (self.name.clone(), link.weight),
-                Some(s) => (s.borrow().name.clone(), link.weight),
-            })
-        }
-        return inputs;
-    }
-
     pub fn reset(&mut self) {
         // shell we drop the links explicitly?
         self.inputs.truncate(0);
     }
 
     pub fn activate(&mut self, w: &mut wyrm::WyrmState, s: &mut simulation::SimulationState) {
+        self.fired = None;
+        if self.disabled {
+            self.potential = 0.0;
+            return;
+        }
         self.potential = w.responsiveness * (self.activate)(self, w, s);
     }
 }
@@ -98,7 +96,7 @@ fn s_age(_: &mut Neuron, w: &mut wyrm::WyrmState, s: &mut simulation::Simulation
 }
 
 fn s_rand(_: &mut Neuron, _: &mut wyrm::WyrmState, _: &mut simulation::SimulationState) -> f32 {
-    rand::random()
+    misc::random()
 }
 fn s_pop(_: &mut Neuron, w: &mut wyrm::WyrmState, s: &mut simulation::SimulationState) -> f32 {
     // population density nearby, where 1 is max density
@@ -126,10 +124,10 @@ fn s_dist_barrier(
     w: &mut wyrm::WyrmState,
     s: &mut simulation::SimulationState,
 ) -> f32 {
-    // distance to barrier
+    // distance to barrier (world edge or wall)
     for t in 0..w.max_dist {
         let (x, y) = (w.x + t * w.dir.0, w.y + t * w.dir.1);
-        if x < 0 || x >= s.size_x || y < 0 || y >= s.size_y {
+        if x < 0 || x >= s.size_x || y < 0 || y >= s.size_y || s.walls[x as usize][y as usize] {
             return 1.0 - (t as f32) / (w.max_dist as f32);
         }
     }
@@ -235,7 +233,8 @@ fn s_good_dist(
 fn a_resp(n: &mut Neuron, w: &mut wyrm::WyrmState, s: &mut simulation::SimulationState) -> f32 {
     // set wyrm responsiveness (how agitated it is)
     let p = tanh_activation(n, w, s);
-    if let Some(sgn) = activate_threshold(&p) {
+    n.fired = activate_threshold(&p);
+    if let Some(sgn) = n.fired {
         w.responsiveness += 0.05 * sgn as f32
     }
     return p;
@@ -243,7 +242,8 @@ fn a_resp(n: &mut Neuron, w: &mut wyrm::WyrmState, s: &mut simulation::Simulatio
 
 fn a_move(n: &mut Neuron, w: &mut wyrm::WyrmState, s: &mut simulation::SimulationState) -> f32 {
     let p = tanh_activation(n, w, s);
-    if let Some(sgn) = activate_threshold(&p) {
+    n.fired = activate_threshold(&p);
+    if let Some(sgn) = n.fired {
         let (mut x, mut y) = (w.x + w.dir.0 * sgn, w.y + w.dir.1 * sgn);
         if x < 0 {
             x = 0
@@ -257,8 +257,8 @@ fn a_move(n: &mut Neuron, w: &mut wyrm::WyrmState, s: &mut simulation::Simulatio
         if y >= s.size_y {
             y = s.size_y - 1
         }
-        if s.world[x as usize][y as usize] {
-            // wyrm ahead, cannot move
+        if s.world[x as usize][y as usize] || s.walls[x as usize][y as usize] {
+            // wyrm or wall ahead, cannot move
             return p;
         }
         s.world[w.x as usize][w.y as usize] = false;
@@ -270,7 +270,8 @@ fn a_move(n: &mut Neuron, w: &mut wyrm::WyrmState, s: &mut simulation::Simulatio
 
 fn a_turn(n: &mut Neuron, w: &mut wyrm::WyrmState, s: &mut simulation::SimulationState) -> f32 {
     let p = tanh_activation(n, w, s);
-    if let Some(sgn) = activate_threshold(&p) {
+    n.fired = activate_threshold(&p);
+    if let Some(sgn) = n.fired {
         match DIRECTIONS
             .iter()
             .enumerate()
@@ -304,7 +305,7 @@ fn tanh_activation(
 }
 
 fn activate_threshold(p: &f32) -> Option<i32> {
-    if rand::random::<f32>() < p.abs() {
+    if misc::random::<f32>() < p.abs() {
         Some(if *p > 0.0 { 1 } else { -1 })
     } else {
         None
@@ -0,0 +1,91 @@
+// Simulation describes what the world looks like as a Scene, and front-ends (SDL window,
+// terminal, offscreen frames) implement Renderer to draw it, so the simulation itself
+// doesn't depend on any of them.
+
+use anyhow::Result;
+
+use crate::frame::{Frame, Rgb};
+
+const SELECTED_COLOR: Rgb = [0xff, 0xff, 0];
+const PATH_COLOR: Rgb = [0xff, 0xff, 0x80];
+
+// what occupies a cell, ordered by priority when several cells share one pixel
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum CellKind {
+    Empty,
+    Selection,
+    Wall,
+    Wyrm,
+}
+
+pub struct Scene {
+    pub cells: Frame,         // one pixel per cell, overlay already applied
+    pub kinds: Vec<CellKind>, // indexed like pixels of `cells`
+    pub selected: Option<(i32, i32)>,
+    pub path: Vec<(i32, i32)>, // where selected wyrm went this generation
+}
+
+pub trait Renderer {
+    fn render(&mut self, scene: &Scene) -> Result<()>;
+}
+
+// renders into a frame in memory, `scale` pixels per cell
+pub struct FrameRenderer {
+    pub scale: u32,
+    pub frame: Frame,
+}
+
+impl FrameRenderer {
+    pub fn new(scale: u32) -> Self {
+        FrameRenderer {
+            scale,
+            frame: Frame::new(0, 0),
+        }
+    }
+}
+
+impl Renderer for FrameRenderer {
+    fn render(&mut self, scene: &Scene) -> Result<()> {
+        let (w, h, s) = (scene.cells.width, scene.cells.height, self.scale as i32);
+        self.frame = Frame::new(w * self.scale, h * self.scale);
+        for (i, px) in scene.cells.pixels.chunks(3).enumerate() {
+            let (x, y) = ((i as u32 % w) as i32, (i as u32 / w) as i32);
+            self.frame
+                .fill_rect(x * s, y * s, s, s, [px[0], px[1], px[2]]);
+        }
+        // path is dotted through the middle of the cells
+        let d = (s / 3).max(1);
+        for (x, y) in &scene.path {
+            self.frame
+                .fill_rect(x * s + (s - d) / 2, y * s + (s - d) / 2, d, d, PATH_COLOR);
+        }
+        if let Some((x, y)) = scene.selected {
+            self.frame.fill_rect(x * s, y * s, s, s, SELECTED_COLOR);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_renderer() {
+        let mut cells = Frame::new(2, 2);
+        cells.set(1, 0, [9, 9, 9]);
+        let scene = Scene {
+            cells,
+            kinds: vec![CellKind::Empty; 4],
+            selected: Some((0, 1)),
+            path: Vec::new(),
+        };
+        let mut r = FrameRenderer::new(2);
+        r.render(&scene).unwrap();
+        assert_eq!((r.frame.width, r.frame.height), (4, 4));
+        let px = |x: usize, y: usize| &r.frame.pixels[(y * 4 + x) * 3..(y * 4 + x) * 3 + 3];
+        assert_eq!(px(3, 1), &[9, 9, 9]);
+        assert_eq!(px(1, 3), &SELECTED_COLOR);
+        assert_eq!(px(0, 0), &[0, 0, 0]);
+    }
+}
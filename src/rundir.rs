@@ -0,0 +1,153 @@
+// Every run writes its outputs into its own directory, together with a manifest describing
+// how the run was started, so results can be told apart and reproduced later.
+
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+
+use crate::config::Config;
+
+#[derive(Serialize)]
+pub struct Manifest {
+    pub version: String,
+    pub started: String, // UTC
+    pub command: Vec<String>,
+    pub seed: u64,
+    pub resumed_from: Option<String>,
+    pub config: Config,
+}
+
+impl Manifest {
+    pub fn new(config: &Config, seed: u64, resumed_from: Option<&str>) -> Self {
+        Manifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started: timestamp(),
+            command: env::args().collect(),
+            seed,
+            resumed_from: resumed_from.map(String::from),
+            config: config.clone(),
+        }
+    }
+}
+
+// manifest of experiments made of many runs, like sweeps, which describe themselves
+#[derive(Serialize)]
+pub struct ExperimentManifest<'a, T: Serialize> {
+    pub version: &'static str,
+    pub started: String,
+    pub command: Vec<String>,
+    #[serde(flatten)]
+    pub experiment: &'a T,
+}
+
+impl<'a, T: Serialize> ExperimentManifest<'a, T> {
+    pub fn new(experiment: &'a T) -> Self {
+        ExperimentManifest {
+            version: env!("CARGO_PKG_VERSION"),
+            started: timestamp(),
+            command: env::args().collect(),
+            experiment,
+        }
+    }
+}
+
+pub struct RunDir {
+    path: PathBuf,
+}
+
+impl RunDir {
+    // creates `base/name`, or `base/run-<date>-<time>` when there's no name.
+    // Named directory must not exist yet, so earlier results are never overwritten
+    pub fn create(base: &str, name: Option<&str>) -> Result<Self> {
+        let path = match name {
+            Some(name) => Path::new(base).join(name),
+            None => {
+                let (y, mo, d, h, mi, s) = utc_now();
+                let stamp = format!("run-{y:04}{mo:02}{d:02}-{h:02}{mi:02}{s:02}");
+                // two runs started within the same second
+                (1..)
+                    .map(|i| match i {
+                        1 => Path::new(base).join(&stamp),
+                        i => Path::new(base).join(format!("{stamp}-{i}")),
+                    })
+                    .find(|p| !p.exists())
+                    .unwrap()
+            }
+        };
+        if path.exists() {
+            return Err(anyhow!("{} already exists", path.display()));
+        }
+        fs::create_dir_all(&path).with_context(|| format!("creating {}", path.display()))?;
+        return Ok(RunDir { path });
+    }
+
+    // outputs go to working directory, e.g. when replaying
+    pub fn current() -> Self {
+        RunDir {
+            path: PathBuf::from("."),
+        }
+    }
+
+    // path of a file in the directory, absolute names are kept as they are
+    pub fn file(&self, name: &str) -> String {
+        self.path.join(name).to_string_lossy().into_owned()
+    }
+
+    pub fn write_manifest<T: Serialize>(&self, manifest: &T) -> Result<()> {
+        let filename = self.file("manifest.json");
+        let json = serde_json::to_string_pretty(manifest)?;
+        fs::write(&filename, json).with_context(|| format!("writing {filename}"))
+    }
+}
+
+impl fmt::Display for RunDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())
+    }
+}
+
+// current UTC time for manifests
+fn timestamp() -> String {
+    let (y, mo, d, h, mi, s) = utc_now();
+    format!("{y:04}-{mo:02}-{d:02} {h:02}:{mi:02}:{s:02}")
+}
+
+fn utc_now() -> (i64, u32, u32, u32, u32, u32) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    return utc(secs);
+}
+
+// year, month, day, hour, minute, second of unix time
+fn utc(secs: i64) -> (i64, u32, u32, u32, u32, u32) {
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400) as u32);
+    // civil from days, shifted so years start in March and leap day is the last one
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    return (y, m, d, rem / 3600, rem % 3600 / 60, rem % 60);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utc() {
+        assert_eq!(utc(0), (1970, 1, 1, 0, 0, 0));
+        assert_eq!(utc(1700000000), (2023, 11, 14, 22, 13, 20));
+        assert_eq!(utc(951825600), (2000, 2, 29, 12, 0, 0));
+    }
+}
@@ -0,0 +1,106 @@
+// Scenario is a hand-made layout of selection area and walls, that is reused by every generation
+// instead of randomly generated selection area.
+// It is stored as plain text, one line per row of the world:
+// '.' is an empty cell, '+' is selection area and '#' is a wall.
+
+use std::fs;
+
+use anyhow::{anyhow, Context, Result};
+
+#[cfg(feature = "sdl")]
+#[derive(Clone, Copy, PartialEq)]
+pub enum Cell {
+    Empty,
+    Selection,
+    Wall,
+}
+
+#[derive(Clone)]
+pub struct Scenario {
+    pub selection_area: Vec<Vec<bool>>,
+    pub walls: Vec<Vec<bool>>,
+}
+
+impl Scenario {
+    pub fn load(filename: &str, size_x: i32, size_y: i32) -> Result<Self> {
+        let text = fs::read_to_string(filename).with_context(|| format!("reading {filename}"))?;
+        return Scenario::parse(&text, size_x, size_y).with_context(|| format!("in {filename}"));
+    }
+
+    pub fn parse(text: &str, size_x: i32, size_y: i32) -> Result<Self> {
+        let mut s = Scenario {
+            selection_area: vec![vec![false; size_y as usize]; size_x as usize],
+            walls: vec![vec![false; size_y as usize]; size_x as usize],
+        };
+        let rows: Vec<&str> = text
+            .lines()
+            .filter(|l| !l.starts_with(';') && !l.is_empty())
+            .collect();
+        if rows.len() != size_y as usize {
+            return Err(anyhow!("expected {size_y} rows, got {}", rows.len()));
+        }
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != size_x as usize {
+                return Err(anyhow!("row {}: expected {size_x} cells", y + 1));
+            }
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '.' => {}
+                    '+' => s.selection_area[x][y] = true,
+                    '#' => s.walls[x][y] = true,
+                    _ => return Err(anyhow!("row {}: unexpected '{c}'", y + 1)),
+                }
+            }
+        }
+        return Ok(s);
+    }
+
+    #[cfg(any(feature = "sdl", test))]
+    pub fn save(&self, filename: &str) -> Result<()> {
+        fs::write(filename, self.to_text()).with_context(|| format!("writing {filename}"))
+    }
+
+    pub fn to_text(&self) -> String {
+        let (size_x, size_y) = (self.walls.len(), self.walls[0].len());
+        let mut text = format!("; wyrmas scenario {size_x}x{size_y}\n");
+        for y in 0..size_y {
+            for x in 0..size_x {
+                text.push(if self.walls[x][y] {
+                    '#'
+                } else if self.selection_area[x][y] {
+                    '+'
+                } else {
+                    '.'
+                });
+            }
+            text.push('\n');
+        }
+        return text;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut s = Scenario {
+            selection_area: vec![vec![false; 3]; 4],
+            walls: vec![vec![false; 3]; 4],
+        };
+        s.selection_area[1][2] = true;
+        s.walls[3][0] = true;
+        let filename = std::env::temp_dir().join("wyrmas-scenario-test.txt");
+        let filename = filename.to_str().unwrap();
+        s.save(filename).unwrap();
+        assert_eq!(
+            fs::read_to_string(filename).unwrap(),
+            "; wyrmas scenario 4x3\n...#\n....\n.+..\n"
+        );
+        let l = Scenario::load(filename, 4, 3).unwrap();
+        assert_eq!(l.selection_area, s.selection_area);
+        assert_eq!(l.walls, s.walls);
+        assert!(Scenario::load(filename, 3, 3).is_err());
+    }
+}
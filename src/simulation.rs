@@ -1,15 +1,45 @@
 use core::f32;
-use rand::seq::SliceRandom;
-use sdl2::{self, libc::DIR, pixels::Color, rect::Rect, render::Canvas, video::Window};
+use std::time::Instant;
 
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "sdl")]
+use crate::scenario::Cell;
 use crate::{
-    genome::Gene,
-    misc::{Dir, DIRECTIONS},
+    ablation::Knockout,
+    config::Config,
+    frame::{Frame, Rgb},
+    genome::{self, Gene},
+    lineage::Lineage,
+    misc::{self, DIRECTIONS},
+    render::{CellKind, Scene},
+    scenario::Scenario,
+    stats::GenerationStats,
+    tracer::Tracer,
+    traces::{Overlay, Traces},
     wyrm::{self, Wyrm},
 };
 
+// how random selection areas are generated
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum, Serialize, Deserialize)]
+pub enum AreaShape {
+    Center, // the same square every generation
+    Spots,  // random walks covering 30% of the world
+    #[default]
+    Rectangles,
+}
+
+// genome of a new wyrm and IDs of its parents
+type Offspring = (Vec<Gene>, Option<(u64, u64)>);
+
 pub struct Simulation {
     pub state: SimulationState,
+    area_shape: AreaShape,
+    pub lineage: Lineage,
+    pub scenario: Option<Scenario>,
+    pub traces: Traces,
+    pub tracer: Option<Tracer>, // records brains of chosen wyrms every tick when set
     wyrmas: Vec<wyrm::Wyrm>,
 }
 
@@ -21,61 +51,78 @@ pub struct SimulationState {
     pub osc_value: f32,
     pub mutation_rate: f32,
     pub tick: i32,
+    pub generation: u64,
     pub world: Vec<Vec<bool>>,
     pub selection_area: Vec<Vec<bool>>,
+    pub walls: Vec<Vec<bool>>,
 }
 
 impl Simulation {
-    pub fn new(
-        size_x: i32,
-        size_y: i32,
-        osc_period: i32,
-        num_inner_neurons: usize,
-        max_age: i32,
-        max_dist: i32,
-        genome_len: usize,
-        population: usize,
-        mutation_rate: f32,
-    ) -> Self {
+    pub fn new(config: &Config) -> Self {
+        let (size_x, size_y) = (config.size_x, config.size_y);
         let mut s = Simulation {
             state: SimulationState {
                 tick: 0,
+                generation: 0,
                 size_x: size_x,
                 size_y: size_y,
-                max_age: max_age,
-                osc_period: osc_period,
+                max_age: config.ticks_per_gen,
+                osc_period: config.osc_period,
                 osc_value: 0.0,
-                mutation_rate: mutation_rate,
+                mutation_rate: config.mutation_rate,
                 world: vec![vec![false; size_y as usize]; size_x as usize],
                 selection_area: vec![vec![false; size_y as usize]; size_x as usize],
+                walls: vec![vec![false; size_y as usize]; size_x as usize],
             },
-            wyrmas: Vec::with_capacity(population),
+            lineage: Lineage::new(),
+            area_shape: config.selection_area,
+            scenario: None,
+            traces: Traces::new(size_x, size_y),
+            tracer: None,
+            wyrmas: Vec::with_capacity(config.population),
         };
 
         // spawn wyrmae
-        for _ in 0..population {
+        for _ in 0..config.population {
             let (x, y) = s.pick_free_cell();
             s.wyrmas.push(Wyrm::new(
+                s.lineage.record(None, 0),
                 x,
                 y,
-                num_inner_neurons,
-                max_dist,
-                (0..genome_len).map(|_| Gene(rand::random())).collect(),
+                config.inner_neurons,
+                config.max_dist,
+                (0..config.genome_size)
+                    .map(|_| Gene(misc::random()))
+                    .collect(),
             ));
         }
+        s.record_traces();
 
         s.create_selection_area();
         return s;
     }
 
     pub fn create_selection_area(&mut self) {
+        if let Some(scenario) = &self.scenario {
+            // hand-made scenario is reused by every generation
+            self.state.selection_area = scenario.selection_area.clone();
+            self.state.walls = scenario.walls.clone();
+            return;
+        }
         self.state
             .selection_area
             .iter_mut()
             .for_each(|col| col.iter_mut().for_each(|c| *c = false));
 
-        /*
-        // selection area in the middle of the world
+        match self.area_shape {
+            AreaShape::Center => self.center_area(),
+            AreaShape::Spots => self.spots_area(),
+            AreaShape::Rectangles => self.rectangles_area(),
+        }
+    }
+
+    // selection area in the middle of the world
+    fn center_area(&mut self) {
         for x in 0..self.state.size_x {
             for y in 0..self.state.size_y {
                 self.state.selection_area[x as usize][y as usize] = x >= self.state.size_x * 3 / 8
@@ -84,19 +131,19 @@ impl Simulation {
                     && y < self.state.size_y * 5 / 8;
             }
         }
-        */
+    }
 
-        // random spots at certain density
-        /*
+    // random spots at certain density
+    fn spots_area(&mut self) {
         let total_area = self.state.size_x as f32 * self.state.size_y as f32;
         let mut ok_count = 0.0;
         loop {
             let (mut sx, mut sy, mut dir) = (
-                (rand::random::<i32>() % self.state.size_x).abs(),
-                (rand::random::<i32>() % self.state.size_y).abs(),
-                DIRECTIONS[rand::random::<usize>() % DIRECTIONS.len()].clone(),
+                (misc::random::<i32>() % self.state.size_x).abs(),
+                (misc::random::<i32>() % self.state.size_y).abs(),
+                DIRECTIONS[misc::random::<usize>() % DIRECTIONS.len()].clone(),
             );
-            for _ in 0..rand::random::<u32>() % 90 {
+            for _ in 0..misc::random::<u32>() % 90 {
                 let (x, y) = (sx + dir.0, sy + dir.1);
                 if x < 0 || x >= self.state.size_x || y < 0 || y >= self.state.size_y {
                     continue;
@@ -107,21 +154,22 @@ impl Simulation {
                 self.state.selection_area[x as usize][y as usize] = true;
                 ok_count += 1.0;
                 (sx, sy) = (x, y);
-                dir = DIRECTIONS[rand::random::<usize>() % DIRECTIONS.len()].clone();
+                dir = DIRECTIONS[misc::random::<usize>() % DIRECTIONS.len()].clone();
                 if ok_count / total_area >= 0.3 {
                     return;
                 }
             }
         }
-        */
+    }
 
-        // random rectangles
+    // random rectangles
+    fn rectangles_area(&mut self) {
         for _ in 0..10 {
             let (sx, sy, w, h) = (
-                rand::random::<u32>() % self.state.size_x as u32,
-                rand::random::<u32>() % self.state.size_y as u32,
-                rand::random::<u32>() % 30,
-                rand::random::<u32>() % 30,
+                misc::random::<u32>() % self.state.size_x as u32,
+                misc::random::<u32>() % self.state.size_y as u32,
+                misc::random::<u32>() % 30,
+                misc::random::<u32>() % 30,
             );
             for x in sx..(sx + w) {
                 for y in sy..sy + h {
@@ -134,14 +182,51 @@ impl Simulation {
         }
     }
 
+    // switches simulation to hand-made scenario (or back to random selection areas)
+    pub fn set_scenario(&mut self, scenario: Option<Scenario>) {
+        self.scenario = scenario;
+        self.create_selection_area();
+        // nobody can stay inside a wall
+        for i in 0..self.wyrmas.len() {
+            let (x, y) = (self.wyrmas[i].state.x, self.wyrmas[i].state.y);
+            if self.state.walls[x as usize][y as usize] {
+                let (nx, ny) = self.pick_free_cell();
+                self.state.world[x as usize][y as usize] = false;
+                (self.wyrmas[i].state.x, self.wyrmas[i].state.y) = (nx, ny);
+            }
+        }
+    }
+
+    // edits world in place, e.g. from the UI
+    #[cfg(feature = "sdl")]
+    pub fn set_cell(&mut self, x: i32, y: i32, cell: Cell) {
+        if x < 0 || x >= self.state.size_x || y < 0 || y >= self.state.size_y {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        self.state.selection_area[x][y] = cell == Cell::Selection;
+        self.state.walls[x][y] = cell == Cell::Wall;
+    }
+
+    // current selection area and walls as a scenario
+    #[cfg(feature = "sdl")]
+    pub fn snapshot_scenario(&self) -> Scenario {
+        Scenario {
+            selection_area: self.state.selection_area.clone(),
+            walls: self.state.walls.clone(),
+        }
+    }
+
     pub fn pick_free_cell(&mut self) -> (i32, i32) {
         let (mut x, mut y): (i32, i32);
         loop {
             (x, y) = (
-                (rand::random::<i32>() % self.state.size_x).abs(),
-                (rand::random::<i32>() % self.state.size_y).abs(),
+                (misc::random::<i32>() % self.state.size_x).abs(),
+                (misc::random::<i32>() % self.state.size_y).abs(),
             );
-            if !self.state.world[x as usize][y as usize] {
+            if !self.state.world[x as usize][y as usize]
+                && !self.state.walls[x as usize][y as usize]
+            {
                 self.state.world[x as usize][y as usize] = true;
                 return (x, y);
             }
@@ -159,6 +244,10 @@ impl Simulation {
         for w in &mut self.wyrmas {
             w.simulation_step(&mut self.state);
         }
+        self.record_traces();
+        if let Some(tracer) = &mut self.tracer {
+            tracer.record(&self.wyrmas, self.state.generation + 1, self.state.tick);
+        }
         return self.state.tick;
     }
 
@@ -177,6 +266,63 @@ impl Simulation {
         self.wyrmas.iter().filter(|w| !w.state.dead).next()
     }
 
+    #[cfg(feature = "sdl")]
+    pub fn get_wyrm(&self, i: usize) -> Option<&Wyrm> {
+        self.wyrmas.get(i)
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn wyrm_at(&self, x: i32, y: i32) -> Option<usize> {
+        self.wyrmas
+            .iter()
+            .position(|w| w.state.x == x && w.state.y == y)
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn find_wyrm(&self, id: u64) -> Option<usize> {
+        self.wyrmas.iter().position(|w| w.state.id == id)
+    }
+
+    // index of first living wyrm that is currently within selection area
+    #[cfg(feature = "sdl")]
+    pub fn find_survivor(&self) -> Option<usize> {
+        self.wyrmas.iter().position(|w| {
+            !w.state.dead && self.state.selection_area[w.state.x as usize][w.state.y as usize]
+        })
+    }
+
+    pub fn population(&self) -> usize {
+        self.wyrmas.len()
+    }
+
+    // mean genetic distance between random pairs of wyrmas
+    pub fn diversity(&self) -> f32 {
+        let samples = 200;
+        let n = self.wyrmas.len();
+        let total: f32 = (0..samples)
+            .map(|_| {
+                let (a, b) = (misc::random::<usize>() % n, misc::random::<usize>() % n);
+                genome::distance(&self.wyrmas[a].state.genome, &self.wyrmas[b].state.genome)
+            })
+            .sum();
+        return total / samples as f32;
+    }
+
+    pub fn selection_area_size(&self) -> usize {
+        self.state
+            .selection_area
+            .iter()
+            .fold(0, |a, ys| a + ys.iter().filter(|v| **v).count())
+    }
+
+    pub fn survivor_ids(&self) -> Vec<u64> {
+        self.wyrmas
+            .iter()
+            .filter(|w| !w.state.dead)
+            .map(|w| w.state.id)
+            .collect()
+    }
+
     pub fn repopulate(&mut self) {
         let mut new_genomes = self.breed_survivors();
         self.state
@@ -186,22 +332,117 @@ impl Simulation {
 
         // reuse old generation by re-placing them randomly
         // and rewiring neurons using new genome
+        self.state.generation += 1;
         for i in 0..new_genomes.len() {
             let (x, y) = self.pick_free_cell();
-            self.wyrmas[i].reset(new_genomes.pop().unwrap(), x, y);
+            let (genome, parents) = new_genomes.pop().unwrap();
+            self.wyrmas[i].reset(genome, x, y);
+            self.wyrmas[i].state.id = self.lineage.record(parents, self.state.generation);
+            self.wyrmas[i].state.parents = parents;
+            self.wyrmas[i].state.born = self.state.generation;
+        }
+        let living: Vec<u64> = self.wyrmas.iter().map(|w| w.state.id).collect();
+        self.lineage.prune(&living);
+        self.state.tick = 0;
+        self.traces.new_generation();
+        self.record_traces();
+    }
+
+    // whole generation without a front-end: living, selection and breeding of the next one
+    pub fn run_generation(&mut self) -> GenerationStats {
+        let start = Instant::now();
+        while self.simulation_step() < self.state.max_age {}
+        let survivors = self.apply_selection();
+        let stats = GenerationStats::new(self, survivors, start.elapsed());
+        self.repopulate();
+        self.create_selection_area();
+        return stats;
+    }
+
+    pub fn genomes(&self) -> Vec<Vec<Gene>> {
+        self.wyrmas.iter().map(|w| w.state.genome.clone()).collect()
+    }
+
+    // replaces population with given genomes (e.g. from a checkpoint), placed randomly.
+    // Lineage starts over, as parents of restored wyrms are not known.
+    pub fn set_genomes(&mut self, genomes: Vec<Vec<Gene>>, generation: u64) {
+        self.state
+            .world
+            .iter_mut()
+            .for_each(|ys| ys.iter_mut().for_each(|v| *v = false));
+        self.state.generation = generation;
+        self.lineage = Lineage::new();
+        for (i, genome) in genomes.into_iter().enumerate().take(self.wyrmas.len()) {
+            let (x, y) = self.pick_free_cell();
+            self.wyrmas[i].reset(genome, x, y);
+            self.wyrmas[i].state.id = self.lineage.record(None, generation);
+            self.wyrmas[i].state.parents = None;
+            self.wyrmas[i].state.born = generation;
         }
         self.state.tick = 0;
+        self.traces.new_generation();
+        self.record_traces();
+    }
+
+    // disables parts of the brain of the first `count` wyrms until knocked out again,
+    // genomes set later are wired without them too
+    pub fn knock_out(&mut self, knockouts: &[Knockout], count: usize) {
+        for w in self.wyrmas.iter_mut().take(count) {
+            w.knock_out(knockouts);
+        }
+    }
+
+    // genome of every wyrm and whether it survived selection
+    pub fn outcomes(&self) -> Vec<(&[Gene], bool)> {
+        self.wyrmas
+            .iter()
+            .map(|w| (w.state.genome.as_slice(), !w.state.dead))
+            .collect()
     }
 
-    fn breed_survivors(&self) -> Vec<Vec<Gene>> {
+    // genomes of randomly chosen wyrms, e.g. to migrate to another population
+    pub fn sample_genomes(&self, n: usize) -> Vec<Vec<Gene>> {
+        perm(self.wyrmas.len())
+            .into_iter()
+            .take(n)
+            .map(|i| self.wyrmas[i].state.genome.clone())
+            .collect()
+    }
+
+    // replaces genomes of randomly chosen wyrms, who stay where they are.
+    // Must be called between generations
+    pub fn replace_genomes(&mut self, genomes: Vec<Vec<Gene>>) {
+        let generation = self.state.generation;
+        for (i, genome) in perm(self.wyrmas.len()).into_iter().zip(genomes) {
+            let w = &mut self.wyrmas[i];
+            let (x, y) = (w.state.x, w.state.y);
+            w.reset(genome, x, y);
+            w.state.id = self.lineage.record(None, generation);
+            w.state.parents = None;
+            w.state.born = generation;
+        }
+    }
+
+    fn record_traces(&mut self) {
+        self.traces.record(
+            self.wyrmas.iter().map(|w| (w.state.x, w.state.y)),
+            self.state.tick,
+        );
+    }
+
+    // returns genomes of new generation along with IDs of their parents
+    fn breed_survivors(&self) -> Vec<Offspring> {
         let survivors: Vec<&Wyrm> = self.wyrmas.iter().filter(|w| !w.state.dead).collect();
         if survivors.is_empty() {
             // nobody survived, generate random gene pool from scratch :(
             return (0..self.wyrmas.len())
                 .map(|_| {
-                    (0..self.wyrmas[0].state.genome.len())
-                        .map(|_| Gene(rand::random()))
-                        .collect::<Vec<Gene>>()
+                    (
+                        (0..self.wyrmas[0].state.genome.len())
+                            .map(|_| Gene(misc::random()))
+                            .collect::<Vec<Gene>>(),
+                        None,
+                    )
                 })
                 .collect();
         }
@@ -213,10 +454,11 @@ impl Simulation {
         // each pair will have at least child_count children
         //survivors.shuffle(&mut rand::thread_rng());
         for i in perm(survivors.len()) {
+            let (a, b) = (survivors[i], survivors[(i + 1) % survivors.len()]);
             new_genomes.extend((0..child_count).map(|_| {
-                survivors[i].breed(
-                    &survivors[(i + 1) % survivors.len()],
-                    &self.state.mutation_rate,
+                (
+                    a.breed(b, &self.state.mutation_rate),
+                    Some((a.state.id, b.state.id)),
                 )
             }));
         }
@@ -226,77 +468,64 @@ impl Simulation {
             .iter()
             .take(self.wyrmas.len() % survivors.len())
         {
-            new_genomes.push(survivors[*i].breed(
-                &survivors[(*i + 1) % survivors.len()],
-                &self.state.mutation_rate,
+            let (a, b) = (survivors[*i], survivors[(*i + 1) % survivors.len()]);
+            new_genomes.push((
+                a.breed(b, &self.state.mutation_rate),
+                Some((a.state.id, b.state.id)),
             ));
         }
 
         return new_genomes;
     }
 
-    pub fn render(&self, canvas: &mut Canvas<Window>, cell_size: i16) {
-        canvas.set_draw_color(Color::BLACK);
-        canvas.clear();
-
-        // draw selection area
-        canvas.set_draw_color(Color::RGB(0, 0x40, 0));
-        for x in 0..self.state.size_x {
-            for y in 0..self.state.size_y {
-                if self.state.selection_area[x as usize][y as usize] {
-                    canvas
-                        .fill_rect(Rect::new(
-                            x * cell_size as i32,
-                            y * cell_size as i32,
-                            cell_size as u32,
-                            cell_size as u32,
-                        ))
-                        .unwrap();
-                }
-            }
+    fn cell_kind(&self, x: usize, y: usize) -> CellKind {
+        if self.state.world[x][y] {
+            return CellKind::Wyrm;
+        } else if self.state.walls[x][y] {
+            return CellKind::Wall;
+        } else if self.state.selection_area[x][y] {
+            return CellKind::Selection;
         }
+        return CellKind::Empty;
+    }
 
-        // draw wyrmas
-        canvas.set_draw_color(Color::RGB(0x80, 0, 0));
-        for w in &self.wyrmas {
-            canvas
-                .fill_rect(Rect::new(
-                    w.state.x * cell_size as i32,
-                    w.state.y * cell_size as i32,
-                    cell_size as u32,
-                    cell_size as u32,
-                ))
-                .unwrap();
-        }
+    fn cell_color(&self, x: usize, y: usize, kind: CellKind, overlay: Overlay) -> Rgb {
+        let base = match kind {
+            CellKind::Wyrm => [0x80, 0, 0],
+            CellKind::Wall => [0x60, 0x60, 0x60],
+            CellKind::Selection => [0, 0x40, 0],
+            CellKind::Empty => [0, 0, 0],
+        };
+        return self.traces.tint(overlay, x, y, base);
+    }
 
-        /*
-        // draw grid
-        for x in 1..self.state.size_x as i16 {
-            canvas
-                .vline(
-                    x * cell_size,
-                    0,
-                    canvas.window().size().1 as i16,
-                    Color::GRAY,
-                )
-                .unwrap();
+    // describes how the world looks to be drawn by any of the renderers
+    pub fn scene(&self, selected: Option<usize>, overlay: Overlay) -> Scene {
+        let (size_x, size_y) = (self.state.size_x as usize, self.state.size_y as usize);
+        let mut cells = Frame::new(size_x as u32, size_y as u32);
+        let mut kinds = vec![CellKind::Empty; size_x * size_y];
+        for x in 0..size_x {
+            for y in 0..size_y {
+                let kind = self.cell_kind(x, y);
+                kinds[y * size_x + x] = kind;
+                cells.set(x as i32, y as i32, self.cell_color(x, y, kind, overlay));
+            }
         }
-        for y in 1..self.state.size_y as i16 {
-            canvas
-                .hline(
-                    0,
-                    canvas.window().size().0 as i16,
-                    y * cell_size,
-                    Color::GRAY,
-                )
-                .unwrap();
+        let selected = selected.filter(|i| *i < self.wyrmas.len());
+        Scene {
+            cells,
+            kinds,
+            selected: selected.map(|i| (self.wyrmas[i].state.x, self.wyrmas[i].state.y)),
+            path: selected
+                .and_then(|i| self.traces.paths.get(i))
+                .cloned()
+                .unwrap_or_default(),
         }
-        */
     }
 }
 
 fn perm(n: usize) -> Vec<usize> {
     let mut r: Vec<usize> = (0..n).collect();
-    r.shuffle(&mut rand::thread_rng());
+    misc::shuffle(&mut r);
     return r;
 }
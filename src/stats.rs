@@ -0,0 +1,83 @@
+use std::{fmt::Display, fs, time::Duration};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::Simulation;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub population: usize,
+    pub survivors: usize,
+    pub selection_area: usize,
+    pub diversity: f32,
+    pub millis: u128,
+    pub ticks_per_sec: f32,
+}
+
+impl GenerationStats {
+    // generation that just ended, after selection was applied
+    pub fn new(sim: &Simulation, survivors: usize, took: Duration) -> Self {
+        GenerationStats {
+            generation: sim.state.generation + 1,
+            population: sim.population(),
+            survivors,
+            selection_area: sim.selection_area_size(),
+            diversity: sim.diversity(),
+            millis: took.as_millis(),
+            ticks_per_sec: sim.state.max_age as f32 / took.as_secs_f32(),
+        }
+    }
+
+    pub fn survival_rate(&self) -> f32 {
+        self.survivors as f32 / self.population as f32
+    }
+
+    // how much of selection area is occupied by survivors
+    pub fn area_taken(&self) -> f32 {
+        self.survivors as f32 / self.selection_area as f32
+    }
+
+    pub fn gens_per_sec(&self) -> f32 {
+        1000.0 / self.millis.max(1) as f32
+    }
+}
+
+impl Display for GenerationStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "generation {}: {} survivors ({:.1}%), ({:.1}% of selection area taken), diversity {:.3}, took {}ms ({:.1} ticks/sec, {:.1} generations/sec)",
+            self.generation,
+            self.survivors,
+            100.0 * self.survival_rate(),
+            100.0 * self.area_taken(),
+            self.diversity,
+            self.millis,
+            self.ticks_per_sec,
+            self.gens_per_sec(),
+        )
+    }
+}
+
+// whole history as CSV, one row per generation
+pub fn write_csv(history: &[GenerationStats], filename: &str) -> Result<()> {
+    let mut csv = String::from(
+        "generation,population,survivors,survival_rate,selection_area,diversity,millis,ticks_per_sec\n",
+    );
+    for s in history {
+        csv.push_str(&format!(
+            "{},{},{},{:.4},{},{:.4},{},{:.1}\n",
+            s.generation,
+            s.population,
+            s.survivors,
+            s.survival_rate(),
+            s.selection_area,
+            s.diversity,
+            s.millis,
+            s.ticks_per_sec
+        ));
+    }
+    fs::write(filename, csv).with_context(|| format!("writing {filename}"))
+}
@@ -0,0 +1,191 @@
+// Stopping criteria for unattended runs. They are checked at the end of every generation,
+// and the reason a run stopped is reported through the process exit code.
+// SIGINT and SIGTERM stop the run too, but they are noticed after every tick.
+
+use std::{
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::stats::GenerationStats;
+
+#[derive(Clone, Default)]
+pub struct StopCriteria {
+    pub generations: Option<u64>,
+    pub time_limit: Option<Duration>,
+    pub survival: Option<f32>, // fraction of population, for `window` generations in a row
+    pub plateau: Option<f32>,  // max diversity change over `window` generations
+    pub window: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StopReason {
+    Converged,
+    Generations,
+    TimeLimit,
+    Interrupted, // by a signal
+    Quit,        // from the UI
+}
+
+impl StopReason {
+    // 0 when run finished as planned, so scripts can tell converged runs from cut short ones
+    pub fn exit_code(self, criteria: &StopCriteria) -> i32 {
+        match self {
+            StopReason::Converged => 0,
+            StopReason::Generations if !criteria.wants_convergence() => 0,
+            StopReason::Generations => 2,
+            StopReason::TimeLimit => 3,
+            StopReason::Interrupted => 130,
+            StopReason::Quit => 0,
+        }
+    }
+}
+
+impl StopCriteria {
+    pub fn wants_convergence(&self) -> bool {
+        self.survival.is_some() || self.plateau.is_some()
+    }
+
+    pub fn check(&self, history: &[GenerationStats], elapsed: Duration) -> Option<StopReason> {
+        if self.converged(history) {
+            return Some(StopReason::Converged);
+        }
+        let generation = history.last().map_or(0, |s| s.generation);
+        if self.generations.is_some_and(|n| generation >= n) {
+            return Some(StopReason::Generations);
+        }
+        if self.time_limit.is_some_and(|t| elapsed >= t) {
+            return Some(StopReason::TimeLimit);
+        }
+        return None;
+    }
+
+    fn converged(&self, history: &[GenerationStats]) -> bool {
+        if self.window == 0 || history.len() < self.window {
+            return false;
+        }
+        let recent = &history[history.len() - self.window..];
+        if let Some(survival) = self.survival {
+            if recent.iter().all(|s| s.survival_rate() >= survival) {
+                return true;
+            }
+        }
+        if let Some(plateau) = self.plateau {
+            let (min, max) = recent.iter().fold((f32::MAX, f32::MIN), |(min, max), s| {
+                (min.min(s.diversity), max.max(s.diversity))
+            });
+            if max - min <= plateau {
+                return true;
+            }
+        }
+        return false;
+    }
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// first signal asks the run to stop after current tick, second one kills it right away
+// in case it's stuck
+pub fn catch_signals() -> Result<()> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            process::exit(130);
+        }
+    })?;
+    Ok(())
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+// "90", "90s", "15m", "2h"
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let num: f64 = num
+        .parse()
+        .map_err(|_| anyhow!("bad duration '{s}', expected e.g. 90s, 15m or 2h"))?;
+    let secs = match unit {
+        "s" => num,
+        "m" => num * 60.0,
+        "h" => num * 3600.0,
+        _ => return Err(anyhow!("bad duration unit in '{s}', expected s, m or h")),
+    };
+    return Duration::try_from_secs_f64(secs)
+        .map_err(|_| anyhow!("bad duration '{s}', expected e.g. 90s, 15m or 2h"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(generation: u64, survivors: usize, diversity: f32) -> GenerationStats {
+        GenerationStats {
+            generation,
+            population: 100,
+            survivors,
+            selection_area: 100,
+            diversity,
+            millis: 1,
+            ticks_per_sec: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let criteria = StopCriteria {
+            generations: Some(5),
+            survival: Some(0.5),
+            window: 2,
+            ..Default::default()
+        };
+        let mut history = vec![stats(1, 60, 0.5), stats(2, 40, 0.5)];
+        assert_eq!(criteria.check(&history, Duration::ZERO), None);
+        history.push(stats(3, 50, 0.5));
+        assert_eq!(criteria.check(&history, Duration::ZERO), None);
+        history.push(stats(4, 70, 0.5));
+        assert_eq!(
+            criteria.check(&history, Duration::ZERO),
+            Some(StopReason::Converged)
+        );
+
+        let history = vec![stats(5, 10, 0.5)];
+        let reason = criteria.check(&history, Duration::ZERO).unwrap();
+        assert_eq!(reason, StopReason::Generations);
+        assert_eq!(reason.exit_code(&criteria), 2);
+
+        let criteria = StopCriteria {
+            plateau: Some(0.01),
+            window: 3,
+            time_limit: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let history = vec![stats(1, 0, 0.5), stats(2, 0, 0.3), stats(3, 0, 0.305)];
+        assert_eq!(
+            criteria.check(&history, Duration::from_secs(61)),
+            Some(StopReason::TimeLimit)
+        );
+        let history = vec![stats(2, 0, 0.3), stats(3, 0, 0.305), stats(4, 0, 0.302)];
+        assert_eq!(
+            criteria.check(&history, Duration::ZERO),
+            Some(StopReason::Converged)
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("15m").unwrap(), Duration::from_secs(900));
+        assert_eq!(parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("99999999999999999999h").is_err());
+        assert!(parse_duration(&"9".repeat(400)).is_err());
+    }
+}
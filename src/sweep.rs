@@ -0,0 +1,204 @@
+// Parameter sweep: every configuration of a grid is evolved with several seeds, each run
+// in its own simulation on one of the worker threads, and survival of every configuration
+// is summarized per generation.
+
+use std::{
+    fs,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{
+    config::Config, misc, scenario::Scenario, simulation::Simulation, stats::GenerationStats,
+};
+
+#[derive(Serialize)]
+pub struct Sweep {
+    pub configs: Vec<Config>,
+    pub seeds: Vec<u64>,
+    pub generations: u64,
+    #[serde(skip)]
+    pub scenario: Option<Scenario>,
+}
+
+// every combination of the values, with the rest taken from `base`
+pub fn grid(
+    base: &Config,
+    mutation_rates: &[f32],
+    genome_sizes: &[usize],
+    inner_neurons: &[usize],
+) -> Vec<Config> {
+    let mut configs = Vec::new();
+    for &mutation_rate in mutation_rates {
+        for &genome_size in genome_sizes {
+            for &inner in inner_neurons {
+                configs.push(Config {
+                    mutation_rate,
+                    genome_size,
+                    inner_neurons: inner,
+                    ..base.clone()
+                });
+            }
+        }
+    }
+    return configs;
+}
+
+// short name of a configuration for file names and progress
+pub fn label(config: &Config) -> String {
+    format!(
+        "m{}-g{}-i{}",
+        config.mutation_rate, config.genome_size, config.inner_neurons
+    )
+}
+
+impl Sweep {
+    // runs every configuration with every seed, `done` is called as runs finish.
+    // Result is indexed by configuration, seed and generation
+    pub fn run(
+        &self,
+        threads: usize,
+        mut done: impl FnMut(&Config, u64, &[GenerationStats]),
+    ) -> Vec<Vec<Vec<GenerationStats>>> {
+        let jobs: Vec<(usize, usize)> = (0..self.configs.len())
+            .flat_map(|c| (0..self.seeds.len()).map(move |s| (c, s)))
+            .collect();
+        let mut results = vec![vec![Vec::new(); self.seeds.len()]; self.configs.len()];
+        // runs are independent, workers only share which job is next
+        let next = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..threads.clamp(1, jobs.len().max(1)) {
+                let tx = tx.clone();
+                let (jobs, next) = (&jobs, &next);
+                scope.spawn(move || {
+                    while let Some(&(c, s)) = jobs.get(next.fetch_add(1, Ordering::SeqCst)) {
+                        let history = self.evolve(&self.configs[c], self.seeds[s]);
+                        if tx.send((c, s, history)).is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+            for (c, s, history) in rx {
+                done(&self.configs[c], self.seeds[s], &history);
+                results[c][s] = history;
+            }
+        });
+        return results;
+    }
+
+    fn evolve(&self, config: &Config, seed: u64) -> Vec<GenerationStats> {
+        misc::seed(seed);
+        let mut sim = Simulation::new(config);
+        if self.scenario.is_some() {
+            sim.set_scenario(self.scenario.clone());
+        }
+        return (0..self.generations)
+            .map(|_| sim.run_generation())
+            .collect();
+    }
+}
+
+// mean and sample standard deviation
+fn mean_stdev(values: &[f32]) -> (f32, f32) {
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    if values.len() < 2 {
+        return (mean, 0.0);
+    }
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / (n - 1.0);
+    return (mean, var.sqrt());
+}
+
+// one row per configuration and generation, aggregated over seeds
+pub fn write_summary(
+    sweep: &Sweep,
+    results: &[Vec<Vec<GenerationStats>>],
+    filename: &str,
+) -> Result<()> {
+    let mut csv = String::from(
+        "mutation_rate,genome_size,inner_neurons,generation,runs,survival_mean,survival_stdev,diversity_mean\n",
+    );
+    for (config, runs) in sweep.configs.iter().zip(results) {
+        for g in 0..sweep.generations as usize {
+            let survival: Vec<f32> = runs.iter().map(|h| h[g].survival_rate()).collect();
+            let diversity: Vec<f32> = runs.iter().map(|h| h[g].diversity).collect();
+            let (mean, stdev) = mean_stdev(&survival);
+            csv.push_str(&format!(
+                "{},{},{},{},{},{:.4},{:.4},{:.4}\n",
+                config.mutation_rate,
+                config.genome_size,
+                config.inner_neurons,
+                g + 1,
+                runs.len(),
+                mean,
+                stdev,
+                mean_stdev(&diversity).0
+            ));
+        }
+    }
+    fs::write(filename, csv).with_context(|| format!("writing {filename}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_stdev() {
+        assert_eq!(mean_stdev(&[0.5]), (0.5, 0.0));
+        let (mean, stdev) = mean_stdev(&[0.2, 0.4, 0.6]);
+        assert!((mean - 0.4).abs() < 1e-6);
+        assert!((stdev - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_grid() {
+        let configs = grid(&Config::default(), &[0.01, 0.1], &[8, 16], &[3]);
+        assert_eq!(configs.len(), 4);
+        assert_eq!(label(&configs[3]), "m0.1-g16-i3");
+    }
+
+    #[test]
+    fn test_write_summary() {
+        let base = Config {
+            size_x: 16,
+            size_y: 16,
+            population: 10,
+            ticks_per_gen: 5,
+            ..Config::default()
+        };
+        let sweep = Sweep {
+            configs: grid(&base, &[0.01], &[4, 8], &[1]),
+            seeds: vec![1, 2],
+            generations: 2,
+            scenario: None,
+        };
+        let mut finished = 0;
+        let results = sweep.run(2, |_, _, history| {
+            assert_eq!(history.len(), 2);
+            finished += 1;
+        });
+        assert_eq!(finished, 4);
+
+        let filename = std::env::temp_dir().join("wyrmas-sweep-test.csv");
+        let filename = filename.to_str().unwrap();
+        write_summary(&sweep, &results, filename).unwrap();
+        let csv = fs::read_to_string(filename).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        // one row for each of 2 configurations and 2 generations
+        assert_eq!(lines.len(), 1 + 2 * 2);
+        let runs = lines[0].split(',').position(|h| h == "runs").unwrap();
+        for line in &lines[1..] {
+            assert_eq!(line.split(',').nth(runs), Some("2"));
+        }
+    }
+}
@@ -0,0 +1,288 @@
+// Activation tracer: potential of every neuron and actions fired by chosen wyrms, recorded
+// every tick to see why a wyrm behaves the way it does. CSV trace has a column per neuron,
+// JSONL trace an object per wyrm and tick.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use anyhow::{anyhow, Context, Error, Result};
+use serde::Serialize;
+
+use crate::{capture::Generations, wyrm::Wyrm};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct Activity {
+    pub neuron: String,
+    pub potential: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fired: Option<i32>, // direction of fired action
+}
+
+// state of one wyrm's brain at the end of a tick
+#[derive(Clone, Serialize, Debug)]
+pub struct Sample {
+    pub generation: u64,
+    pub tick: i32,
+    pub wyrm: usize, // index in population
+    pub id: u64,
+    pub x: i32,
+    pub y: i32,
+    pub neurons: Vec<Activity>, // sensors, inner neurons, then actions
+}
+
+impl Sample {
+    pub fn new(wyrm: &Wyrm, index: usize, generation: u64, tick: i32) -> Self {
+        Sample {
+            generation,
+            tick,
+            wyrm: index,
+            id: wyrm.state.id,
+            x: wyrm.state.x,
+            y: wyrm.state.y,
+            neurons: wyrm
+                .brain()
+                .nodes
+                .into_iter()
+                .map(|n| Activity {
+                    neuron: n.name,
+                    potential: n.potential,
+                    fired: n.fired,
+                })
+                .collect(),
+        }
+    }
+
+    fn csv_header(&self) -> String {
+        let neurons: Vec<&str> = self.neurons.iter().map(|a| a.neuron.as_str()).collect();
+        format!("generation,tick,wyrm,id,x,y,{},fired", neurons.join(","))
+    }
+
+    // fired actions are listed in the last column with sign of their direction, e.g. "+move -turn"
+    fn csv_row(&self) -> String {
+        let potentials: Vec<String> = self
+            .neurons
+            .iter()
+            .map(|a| format!("{:.4}", a.potential))
+            .collect();
+        let fired: Vec<String> = self
+            .neurons
+            .iter()
+            .filter_map(|a| {
+                a.fired
+                    .map(|d| format!("{}{}", if d > 0 { '+' } else { '-' }, a.neuron))
+            })
+            .collect();
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.generation,
+            self.tick,
+            self.wyrm,
+            self.id,
+            self.x,
+            self.y,
+            potentials.join(","),
+            fired.join(" ")
+        )
+    }
+}
+
+pub struct Tracer {
+    pub wyrms: Vec<usize>,            // indices of traced wyrms
+    generations: Option<Generations>, // all if not set
+    format: Format,
+    out: Box<dyn Write>,
+    header: bool,         // CSV header was written
+    error: Option<Error>, // first write error, tracing stops there
+}
+
+impl Tracer {
+    pub fn new(
+        out: Box<dyn Write>,
+        format: Format,
+        wyrms: Vec<usize>,
+        generations: Option<Generations>,
+    ) -> Self {
+        Tracer {
+            wyrms,
+            generations,
+            format,
+            out,
+            header: false,
+            error: None,
+        }
+    }
+
+    // format is given by extension, csv or jsonl
+    pub fn create(
+        filename: &str,
+        wyrms: Vec<usize>,
+        generations: Option<Generations>,
+    ) -> Result<Self> {
+        let format = match filename.rsplit('.').next() {
+            Some("csv") => Format::Csv,
+            Some("jsonl") => Format::Jsonl,
+            _ => return Err(anyhow!("trace {filename} should be *.csv or *.jsonl")),
+        };
+        let file = File::create(filename).with_context(|| format!("creating {filename}"))?;
+        Ok(Tracer::new(
+            Box::new(BufWriter::new(file)),
+            format,
+            wyrms,
+            generations,
+        ))
+    }
+
+    // called after every tick with the whole population
+    pub fn record(&mut self, population: &[Wyrm], generation: u64, tick: i32) {
+        if self.error.is_some()
+            || self
+                .generations
+                .as_ref()
+                .is_some_and(|g| !g.contains(generation))
+        {
+            return;
+        }
+        for i in 0..self.wyrms.len() {
+            let index = self.wyrms[i];
+            if let Some(wyrm) = population.get(index) {
+                if let Err(err) = self.write(&Sample::new(wyrm, index, generation, tick)) {
+                    self.error = Some(err);
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn write(&mut self, sample: &Sample) -> Result<()> {
+        match self.format {
+            Format::Csv => {
+                if !self.header {
+                    self.header = true;
+                    writeln!(self.out, "{}", sample.csv_header())?;
+                }
+                writeln!(self.out, "{}", sample.csv_row())?;
+            }
+            Format::Jsonl => writeln!(self.out, "{}", serde_json::to_string(sample)?)?,
+        }
+        Ok(())
+    }
+
+    // flushes the file, reports error of any earlier write
+    pub fn finish(mut self) -> Result<()> {
+        if let Some(err) = self.error.take() {
+            return Err(err.context("writing trace"));
+        }
+        self.out.flush().context("writing trace")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{config::Config, genome::Gene, misc, simulation::Simulation};
+
+    // lets the test read what tracer wrote into a boxed writer
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        misc::seed(1);
+        let config = Config {
+            size_x: 32,
+            size_y: 32,
+            population: 3,
+            ticks_per_gen: 5,
+            genome_size: 1,
+            ..Config::default()
+        };
+        let mut sim = Simulation::new(&config);
+        let out = Shared::default();
+        let gens = Generations::parse("1").unwrap();
+        sim.tracer = Some(Tracer::new(
+            Box::new(out.clone()),
+            Format::Csv,
+            vec![1],
+            Some(gens),
+        ));
+        // dist_to_nearest_cell -> move, nearly always fires as wyrms are far apart
+        let genomes = vec![vec![Gene(0x0401ffff)]; 3];
+        for generation in 0..2 {
+            sim.set_genomes(genomes.clone(), generation);
+            while sim.simulation_step() < config.ticks_per_gen {}
+        }
+        sim.tracer.take().unwrap().finish().unwrap();
+
+        let text = String::from_utf8(out.0.borrow().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        let header: Vec<&str> = lines[0].split(',').collect();
+        let column = |name: &str| header.iter().position(|h| *h == name).unwrap();
+        // only wyrm 1 and only generation 1, one row per tick
+        assert_eq!(lines.len(), 1 + 5);
+        for (tick, line) in lines[1..].iter().enumerate() {
+            let row: Vec<&str> = line.split(',').collect();
+            assert_eq!(&row[..3], &["1", &(tick + 1).to_string(), "1"]);
+            let potential: f32 = row[column("move")].parse().unwrap();
+            assert!(potential > 0.9);
+            assert_eq!(row[column("turn")], "0.0000");
+        }
+        let fired = column("fired");
+        assert!(lines[1..]
+            .iter()
+            .any(|l| l.split(',').nth(fired) == Some("+move")));
+    }
+
+    #[test]
+    fn test_csv() {
+        let activity = |neuron: &str, potential, fired| Activity {
+            neuron: String::from(neuron),
+            potential,
+            fired,
+        };
+        let sample = Sample {
+            generation: 3,
+            tick: 7,
+            wyrm: 1,
+            id: 42,
+            x: 5,
+            y: 6,
+            neurons: vec![
+                activity("age", 0.5, None),
+                activity("move", -0.25, Some(-1)),
+                activity("turn", 0.125, Some(1)),
+            ],
+        };
+        assert_eq!(
+            sample.csv_header(),
+            "generation,tick,wyrm,id,x,y,age,move,turn,fired"
+        );
+        assert_eq!(
+            sample.csv_row(),
+            "3,7,1,42,5,6,0.5000,-0.2500,0.1250,-move +turn"
+        );
+        let json = serde_json::to_string(&sample.neurons[..2]).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"neuron":"age","potential":0.5},{"neuron":"move","potential":-0.25,"fired":-1}]"#
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// Traces remember where wyrms have been: path of every wyrm during current generation,
+// tick when each cell was last occupied (for fading trails) and how many ticks each cell
+// was occupied over the whole run (heatmap).
+
+use clap::ValueEnum;
+
+use crate::frame::Rgb;
+
+const TRAIL_LEN: i32 = 20; // ticks until trail fades out completely
+const TRAIL_COLOR: Rgb = [0xff, 0x90, 0x20];
+
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+pub enum Overlay {
+    None,
+    Trails,
+    Heatmap,
+}
+
+impl Overlay {
+    pub fn next(self) -> Overlay {
+        match self {
+            Overlay::None => Overlay::Trails,
+            Overlay::Trails => Overlay::Heatmap,
+            Overlay::Heatmap => Overlay::None,
+        }
+    }
+}
+
+pub struct Traces {
+    pub paths: Vec<Vec<(i32, i32)>>, // indexed same as wyrms
+    last_visit: Vec<Vec<i32>>,       // -1 if not visited this generation
+    heatmap: Vec<Vec<u32>>,
+    heat_max: u32,
+    tick: i32,
+}
+
+impl Traces {
+    pub fn new(size_x: i32, size_y: i32) -> Self {
+        Traces {
+            paths: Vec::new(),
+            last_visit: vec![vec![-1; size_y as usize]; size_x as usize],
+            heatmap: vec![vec![0; size_y as usize]; size_x as usize],
+            heat_max: 0,
+            tick: 0,
+        }
+    }
+
+    // forgets paths and trails, heatmap is kept for the whole run
+    pub fn new_generation(&mut self) {
+        self.paths.clear();
+        self.last_visit
+            .iter_mut()
+            .for_each(|ys| ys.iter_mut().for_each(|v| *v = -1));
+    }
+
+    pub fn record(&mut self, positions: impl Iterator<Item = (i32, i32)>, tick: i32) {
+        self.tick = tick;
+        for (i, (x, y)) in positions.enumerate() {
+            if i >= self.paths.len() {
+                self.paths.push(Vec::new());
+            }
+            // wyrms standing still don't make their path longer
+            if self.paths[i].last() != Some(&(x, y)) {
+                self.paths[i].push((x, y));
+            }
+            self.last_visit[x as usize][y as usize] = tick;
+            let heat = &mut self.heatmap[x as usize][y as usize];
+            *heat += 1;
+            self.heat_max = self.heat_max.max(*heat);
+        }
+    }
+
+    // colour of the cell with overlay applied on top of its normal colour
+    pub fn tint(&self, overlay: Overlay, x: usize, y: usize, base: Rgb) -> Rgb {
+        match overlay {
+            Overlay::None => base,
+            Overlay::Trails => {
+                let last = self.last_visit[x][y];
+                let age = self.tick - last;
+                if last < 0 || age == 0 || age >= TRAIL_LEN {
+                    return base;
+                }
+                let f = 0.8 * (1.0 - age as f32 / TRAIL_LEN as f32);
+                blend(base, TRAIL_COLOR, f)
+            }
+            Overlay::Heatmap => {
+                if self.heat_max == 0 || self.heatmap[x][y] == 0 {
+                    return blend(base, [0, 0, 0], 0.5);
+                }
+                // log scale, otherwise few crowded cells make everything else black
+                let v = (1.0 + self.heatmap[x][y] as f32).ln() / (1.0 + self.heat_max as f32).ln();
+                heat_color(v)
+            }
+        }
+    }
+}
+
+fn blend(a: Rgb, b: Rgb, f: f32) -> Rgb {
+    let mix = |a: u8, b: u8| (a as f32 * (1.0 - f) + b as f32 * f) as u8;
+    [mix(a[0], b[0]), mix(a[1], b[1]), mix(a[2], b[2])]
+}
+
+// dark blue -> red -> yellow -> white
+fn heat_color(v: f32) -> Rgb {
+    let stops: [Rgb; 4] = [
+        [0x10, 0x10, 0x60],
+        [0xd0, 0x20, 0x20],
+        [0xff, 0xe0, 0x20],
+        [0xff; 3],
+    ];
+    let pos = v.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let i = (pos as usize).min(stops.len() - 2);
+    return blend(stops[i], stops[i + 1], pos - i as f32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trails() {
+        let mut t = Traces::new(4, 4);
+        t.record([(0, 0), (3, 3)].into_iter(), 0);
+        t.record([(1, 0), (3, 3)].into_iter(), 1);
+        assert_eq!(t.paths, vec![vec![(0, 0), (1, 0)], vec![(3, 3)]]);
+        let base = [0, 0, 0];
+        // old position fades, current one and unvisited ones are untouched
+        assert_ne!(t.tint(Overlay::Trails, 0, 0, base), base);
+        assert_eq!(t.tint(Overlay::Trails, 1, 0, base), base);
+        assert_eq!(t.tint(Overlay::Trails, 2, 2, base), base);
+        // cell occupied on both ticks is the hottest
+        assert_eq!(t.tint(Overlay::Heatmap, 3, 3, base), [0xff; 3]);
+
+        t.new_generation();
+        assert!(t.paths.is_empty());
+        assert_eq!(t.tint(Overlay::Trails, 0, 0, base), base);
+        assert_eq!(t.tint(Overlay::Heatmap, 3, 3, base), [0xff; 3]);
+    }
+}
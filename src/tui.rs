@@ -0,0 +1,273 @@
+// Terminal front-end for when there's no display, e.g. over SSH.
+// World is downsampled so it fits the terminal, two world pixels per character
+// using upper half block with separate foreground and background colours.
+
+use std::{
+    io::{self, Stdout, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    queue,
+    style::{self, Color},
+    terminal,
+};
+
+use crate::{
+    command::{Command, Step},
+    frame::Rgb,
+    render::{Renderer, Scene},
+    simulation::Simulation,
+    stats::GenerationStats,
+    traces::Overlay,
+};
+
+const STATS_H: u16 = 4; // lines below the world
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct Tui {
+    out: Stdout,
+    last_frame: Instant,
+    pub overlay: Overlay,
+    pub paused: bool,
+    message: String,
+}
+
+// draws the world scaled down to fit above the stats
+impl Renderer for Tui {
+    fn render(&mut self, scene: &Scene) -> Result<()> {
+        let (cols, rows) = terminal::size()?;
+        let rows = rows.saturating_sub(STATS_H).max(1);
+        let frame = &scene.cells;
+
+        // keep aspect ratio, characters are roughly twice as tall as wide
+        let scale = f32::max(
+            frame.width as f32 / cols as f32,
+            frame.height as f32 / (2 * rows) as f32,
+        );
+        let (w, h) = (
+            (frame.width as f32 / scale) as u16,
+            (frame.height as f32 / scale / 2.0) as u16,
+        );
+        let mut colors = None;
+        for row in 0..rows {
+            queue!(self.out, cursor::MoveTo(0, row))?;
+            if row >= h {
+                queue!(
+                    self.out,
+                    style::ResetColor,
+                    terminal::Clear(terminal::ClearType::UntilNewLine)
+                )?;
+                colors = None;
+                continue;
+            }
+            for col in 0..w {
+                let top = sample(scene, scale, col, 2 * row);
+                let bottom = sample(scene, scale, col, 2 * row + 1);
+                if colors != Some((top, bottom)) {
+                    queue!(
+                        self.out,
+                        style::SetForegroundColor(rgb(top)),
+                        style::SetBackgroundColor(rgb(bottom))
+                    )?;
+                    colors = Some((top, bottom));
+                }
+                queue!(self.out, style::Print('▀'))?;
+            }
+            queue!(
+                self.out,
+                style::ResetColor,
+                terminal::Clear(terminal::ClearType::UntilNewLine)
+            )?;
+            colors = None;
+        }
+        Ok(())
+    }
+}
+
+pub fn init_tui() -> Result<Tui> {
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    crossterm::execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+    Ok(Tui {
+        out,
+        last_frame: Instant::now(),
+        overlay: Overlay::None,
+        paused: false,
+        message: String::from(
+            "q quit, space pause, . tick, g generation, +/- speed, a animate, t overlay, h hall of fame, l lineage",
+        ),
+    })
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        // give the terminal back even if we're going down because of an error
+        let _ = crossterm::execute!(
+            self.out,
+            style::ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Tui {
+    // shown in the bottom line instead of printing, which would mess up the screen
+    pub fn message(&mut self, text: String) {
+        self.message = text;
+    }
+
+    pub fn draw(&mut self, sim: &Simulation, history: &[GenerationStats], fps: u32) -> Result<()> {
+        self.render(&sim.scene(None, self.overlay))?;
+        let (cols, rows) = terminal::size()?;
+        let rows = rows.saturating_sub(STATS_H).max(1);
+        let mut lines = vec![format!(
+            "generation {}  tick {}/{}  population {}{}",
+            sim.state.generation + 1,
+            sim.state.tick,
+            sim.state.max_age,
+            sim.population(),
+            if self.paused { "  [paused]" } else { "" },
+        )];
+        match history.last() {
+            Some(last) => lines.push(format!(
+                "last: {} survivors ({:.1}%), area taken {:.1}%, diversity {:.3}, {:.1} ticks/sec",
+                last.survivors,
+                100.0 * last.survival_rate(),
+                100.0 * last.area_taken(),
+                last.diversity,
+                last.ticks_per_sec
+            )),
+            None => lines.push(String::new()),
+        }
+        // survival rate of recent generations, one character each
+        let width = (cols as usize).saturating_sub(10);
+        let spark: String = history
+            .iter()
+            .skip(history.len().saturating_sub(width))
+            .map(|s| SPARKS[((s.survival_rate() * 8.0) as usize).min(7)])
+            .collect();
+        lines.push(format!("survival {spark}"));
+        lines.push(self.message.clone());
+        for (i, line) in lines.iter().enumerate() {
+            let line: String = line.chars().take(cols as usize).collect();
+            queue!(
+                self.out,
+                cursor::MoveTo(0, rows + i as u16),
+                style::Print(line),
+                terminal::Clear(terminal::ClearType::UntilNewLine)
+            )?;
+        }
+        self.out.flush()?;
+
+        if fps > 0 {
+            let frame = Duration::from_secs(1) / fps;
+            let elapsed = self.last_frame.elapsed();
+            if elapsed < frame {
+                thread::sleep(frame - elapsed);
+            }
+        }
+        self.last_frame = Instant::now();
+        Ok(())
+    }
+
+    pub fn poll(&mut self) -> Vec<Command> {
+        let mut commands = Vec::new();
+        while let Ok(true) = event::poll(Duration::ZERO) {
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    commands.push(Command::Interrupt)
+                }
+                KeyCode::Char('q') | KeyCode::Esc => commands.push(Command::Quit),
+                KeyCode::Char(' ') => commands.push(Command::Pause),
+                KeyCode::Char('.') => commands.push(Command::Step(Step::Tick)),
+                KeyCode::Char('g') => commands.push(Command::Step(Step::Generation)),
+                KeyCode::Char('+' | '=') => commands.push(Command::Faster),
+                KeyCode::Char('-') => commands.push(Command::Slower),
+                KeyCode::Char('a') => commands.push(Command::Animate),
+                KeyCode::Char('h') => commands.push(Command::Reinject),
+                KeyCode::Char('l') => commands.push(Command::Lineage),
+                KeyCode::Char('t') => {
+                    self.overlay = self.overlay.next();
+                    self.message = format!("overlay {:?}", self.overlay);
+                }
+                _ => {}
+            }
+        }
+        return commands;
+    }
+}
+
+// picks the colour of the most important cell among those covered by one half-character
+// (wyrm, then wall, then selection area), so single wyrms don't disappear when the world
+// is scaled down
+fn sample(scene: &Scene, scale: f32, col: u16, row: u16) -> Rgb {
+    let frame = &scene.cells;
+    let (x0, y0) = ((col as f32 * scale) as u32, (row as f32 * scale) as u32);
+    let (x1, y1) = (
+        (((col + 1) as f32 * scale) as u32).clamp(x0 + 1, frame.width),
+        (((row + 1) as f32 * scale) as u32).clamp(y0 + 1, frame.height),
+    );
+    let mut best = (y0 * frame.width + x0) as usize;
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let i = (y * frame.width + x) as usize;
+            if scene.kinds[i] > scene.kinds[best] {
+                best = i;
+            }
+        }
+    }
+    let p = &frame.pixels[best * 3..best * 3 + 3];
+    return [p[0], p[1], p[2]];
+}
+
+fn rgb(c: Rgb) -> Color {
+    Color::Rgb {
+        r: c[0],
+        g: c[1],
+        b: c[2],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{frame::Frame, render::CellKind};
+
+    #[test]
+    fn test_sample() {
+        // wyrm, wall, selection and empty cell all squeezed into one half-character
+        let mut cells = Frame::new(2, 2);
+        cells.set(1, 0, [0x60, 0x60, 0x60]);
+        cells.set(0, 1, [0x80, 0, 0]);
+        cells.set(1, 1, [0, 0x40, 0]);
+        let mut scene = Scene {
+            cells,
+            kinds: vec![
+                CellKind::Empty,
+                CellKind::Wall,
+                CellKind::Wyrm,
+                CellKind::Selection,
+            ],
+            selected: None,
+            path: Vec::new(),
+        };
+        assert_eq!(sample(&scene, 2.0, 0, 0), [0x80, 0, 0]);
+        scene.kinds[2] = CellKind::Empty;
+        assert_eq!(sample(&scene, 2.0, 0, 0), [0x60, 0x60, 0x60]);
+        scene.kinds[1] = CellKind::Empty;
+        assert_eq!(sample(&scene, 2.0, 0, 0), [0, 0x40, 0]);
+    }
+}
@@ -0,0 +1,583 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use sdl2::{
+    event::{Event, WindowEvent},
+    gfx::primitives::DrawRenderer,
+    keyboard::{Keycode, Scancode},
+    mouse::MouseButton,
+    pixels::{Color, PixelFormatEnum},
+    rect::{Point, Rect},
+    render::Canvas,
+    video::{Window, WindowPos},
+    EventPump, VideoSubsystem,
+};
+
+use crate::{
+    brainview,
+    command::{Command, Step},
+    render::{Renderer, Scene},
+    scenario::Cell,
+    simulation::Simulation,
+    stats::GenerationStats,
+    traces::Overlay,
+};
+
+const TOOLTIP_H: i32 = 24;
+pub const PANEL_W: i32 = 240;
+const CHART_H: i32 = 160;
+const MAX_WINDOW: (i32, i32) = (1280, 960);
+
+// maps world cells to window pixels
+pub struct Camera {
+    pub scale: f32, // pixels per cell
+    pub x: f32,     // world coordinates of top left corner of the window
+    pub y: f32,
+}
+
+impl Camera {
+    pub fn to_world(&self, sx: i32, sy: i32) -> (i32, i32) {
+        (
+            (self.x + sx as f32 / self.scale).floor() as i32,
+            (self.y + sy as f32 / self.scale).floor() as i32,
+        )
+    }
+
+    // where whole world of given size ends up on the screen
+    pub fn view(&self, size_x: i32, size_y: i32) -> Rect {
+        Rect::new(
+            (-self.x * self.scale).round() as i32,
+            (-self.y * self.scale).round() as i32,
+            ((size_x as f32 * self.scale).round() as u32).max(1),
+            ((size_y as f32 * self.scale).round() as u32).max(1),
+        )
+    }
+
+    // zooms keeping the point under the cursor in place
+    pub fn zoom_at(&mut self, sx: i32, sy: i32, factor: f32) {
+        let (wx, wy) = (
+            self.x + sx as f32 / self.scale,
+            self.y + sy as f32 / self.scale,
+        );
+        self.scale = (self.scale * factor).clamp(0.05, 64.0);
+        self.x = wx - sx as f32 / self.scale;
+        self.y = wy - sy as f32 / self.scale;
+    }
+
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.x -= dx as f32 / self.scale;
+        self.y -= dy as f32 / self.scale;
+    }
+
+    // shows whole world centered in the viewport
+    pub fn fit(&mut self, (size_x, size_y): (i32, i32), (w, h): (i32, i32)) {
+        self.scale = f32::min(w as f32 / size_x as f32, h as f32 / size_y as f32);
+        self.x = (size_x as f32 - w as f32 / self.scale) / 2.0;
+        self.y = (size_y as f32 - h as f32 / self.scale) / 2.0;
+    }
+}
+
+pub struct UI {
+    pub canvas: Canvas<Window>,
+    pub events: EventPump,
+    video: VideoSubsystem,
+    viewer: Option<BrainViewer>,
+    last_frame: Instant,
+    pub editing: bool,
+    last_paint: Option<(i32, i32)>,
+    pub camera: Camera,
+    pub overlay: Overlay,
+    world: (i32, i32),
+    drag: Option<(i32, i32)>,
+}
+
+// separate window showing live network of a single wyrm
+struct BrainViewer {
+    canvas: Canvas<Window>,
+    wyrm: usize,
+    mouse: Option<(i32, i32)>,
+}
+
+// draws the world where camera looks.
+// World is rendered one pixel per cell into a texture first, so huge worlds
+// are drawn as fast as small ones at any zoom level.
+impl Renderer for UI {
+    fn render(&mut self, scene: &Scene) -> Result<()> {
+        let cells = &scene.cells;
+        let view = self.camera.view(cells.width as i32, cells.height as i32);
+        let texture_creator = self.canvas.texture_creator();
+        let mut texture = texture_creator
+            .create_texture_streaming(PixelFormatEnum::RGB24, cells.width, cells.height)
+            .map_err(|e| anyhow!(e))?;
+        texture
+            .update(None, &cells.pixels, cells.width as usize * 3)
+            .map_err(|e| anyhow!(e))?;
+        let canvas = &mut self.canvas;
+        canvas.copy(&texture, None, view).map_err(|e| anyhow!(e))?;
+
+        // highlight selected wyrm along with the path it took this generation
+        let scale = self.camera.scale;
+        let to_screen = |(x, y): (i32, i32), offset: f32| {
+            Point::new(
+                view.x() + ((x as f32 + offset) * scale) as i32,
+                view.y() + ((y as f32 + offset) * scale) as i32,
+            )
+        };
+        if scene.path.len() > 1 {
+            let points: Vec<Point> = scene.path.iter().map(|p| to_screen(*p, 0.5)).collect();
+            canvas.set_draw_color(Color::RGB(0xff, 0xff, 0x80));
+            canvas
+                .draw_lines(points.as_slice())
+                .map_err(|e| anyhow!(e))?;
+        }
+        if let Some(p) = scene.selected {
+            let c = scale.max(1.0) as i32;
+            let p = to_screen(p, 0.0);
+            canvas.set_draw_color(Color::YELLOW);
+            canvas
+                .fill_rect(Rect::new(p.x, p.y, c as u32, c as u32))
+                .map_err(|e| anyhow!(e))?;
+            // keep the frame visible even when zoomed out
+            let r = c.max(4);
+            canvas
+                .draw_rect(Rect::new(
+                    p.x - r,
+                    p.y - r,
+                    (2 * r + c) as u32,
+                    (2 * r + c) as u32,
+                ))
+                .map_err(|e| anyhow!(e))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn init_ui(visualize: bool, size_x: i32, size_y: i32, cell_size: i32) -> Option<UI> {
+    if !visualize {
+        return None;
+    }
+    // large worlds won't fit the screen, so window is limited in size and zoomed out
+    let (w, h) = (
+        (size_x * cell_size).min(MAX_WINDOW.0),
+        (size_y * cell_size).min(MAX_WINDOW.1),
+    );
+    let mut camera = Camera {
+        scale: cell_size as f32,
+        x: 0.0,
+        y: 0.0,
+    };
+    if w < size_x * cell_size || h < size_y * cell_size {
+        camera.fit((size_x, size_y), (w, h));
+    }
+
+    let sdl_context = sdl2::init().unwrap();
+    let events = sdl_context.event_pump().unwrap();
+    let video_subsystem = sdl_context.video().unwrap();
+    let window = video_subsystem
+        .window("wyrmas", (w + PANEL_W) as u32, h as u32)
+        .position_centered()
+        .resizable()
+        .build()
+        .unwrap();
+    let mut canvas = window.into_canvas().build().unwrap();
+    canvas.clear();
+    canvas.present();
+
+    return Some(UI {
+        canvas: canvas,
+        events: events,
+        video: video_subsystem,
+        viewer: None,
+        last_frame: Instant::now(),
+        editing: false,
+        last_paint: None,
+        camera,
+        overlay: Overlay::None,
+        world: (size_x, size_y),
+        drag: None,
+    });
+}
+
+impl UI {
+    // shows rendered frame, waiting if needed to keep given frame rate (0 means no limit)
+    pub fn present(&mut self, fps: u32) {
+        self.canvas.present();
+        if fps > 0 {
+            let frame = Duration::from_secs(1) / fps;
+            let elapsed = self.last_frame.elapsed();
+            if elapsed < frame {
+                thread::sleep(frame - elapsed);
+            }
+        }
+        self.last_frame = Instant::now();
+    }
+
+    // size of the area where world is drawn, that is window without stats panel
+    fn viewport(&self) -> (i32, i32) {
+        let (w, h) = self.canvas.output_size().unwrap_or((1, 1));
+        ((w as i32 - PANEL_W).max(1), h as i32)
+    }
+
+    // draws the world and stats panel to the right of it
+    pub fn draw(&mut self, sim: &Simulation, selected: Option<usize>, history: &[GenerationStats]) {
+        self.canvas.set_draw_color(Color::BLACK);
+        self.canvas.clear();
+        if let Err(err) = self.render(&sim.scene(selected, self.overlay)) {
+            println!("error drawing world: {err:#}");
+        }
+        let x0 = self.viewport().0;
+        if let Err(err) = self.draw_stats(sim, history, x0) {
+            println!("error drawing stats: {err}");
+        }
+    }
+
+    fn draw_stats(
+        &mut self,
+        sim: &Simulation,
+        history: &[GenerationStats],
+        x0: i32,
+    ) -> Result<(), String> {
+        let canvas = &mut self.canvas;
+        let h = canvas.output_size()?.1 as i32;
+        canvas.set_draw_color(Color::RGB(0x20, 0x20, 0x20));
+        canvas.fill_rect(Rect::new(x0, 0, PANEL_W as u32, h as u32))?;
+
+        let text = Color::RGB(0xc0, 0xc0, 0xc0);
+        let mut lines = vec![
+            format!("generation {}", sim.state.generation + 1),
+            format!("tick {}/{}", sim.state.tick, sim.state.max_age),
+            format!("population {}", sim.population()),
+        ];
+        if let Some(last) = history.last() {
+            lines.push(String::new());
+            lines.push(format!("last generation {}:", last.generation));
+            lines.push(format!(
+                " survivors {} ({:.1}%)",
+                last.survivors,
+                100.0 * last.survival_rate()
+            ));
+            lines.push(format!(" area taken {:.1}%", 100.0 * last.area_taken()));
+            lines.push(format!(" diversity {:.3}", last.diversity));
+            lines.push(format!(" {:.1} ticks/sec", last.ticks_per_sec));
+        }
+        for (i, line) in lines.iter().enumerate() {
+            canvas.string((x0 + 10) as i16, (10 + i * 14) as i16, line, text)?;
+        }
+
+        // scrolling chart of survival rate, one pixel per generation
+        let (cx, cy, cw) = (x0 + 10, h - CHART_H - 20, PANEL_W - 20);
+        canvas.string(cx as i16, (cy - 14) as i16, "survival %", text)?;
+        for pct in [25, 50, 75] {
+            let y = cy + CHART_H - CHART_H * pct / 100;
+            canvas.hline(
+                cx as i16,
+                (cx + cw) as i16,
+                y as i16,
+                Color::RGB(0x40, 0x40, 0x40),
+            )?;
+        }
+        canvas.rectangle(
+            cx as i16,
+            cy as i16,
+            (cx + cw) as i16,
+            (cy + CHART_H) as i16,
+            text,
+        )?;
+        let points: Vec<(i16, i16)> = history
+            .iter()
+            .skip(history.len().saturating_sub(cw as usize))
+            .enumerate()
+            .map(|(i, s)| {
+                (
+                    (cx + i as i32) as i16,
+                    (cy + CHART_H - (CHART_H as f32 * s.survival_rate()) as i32) as i16,
+                )
+            })
+            .collect();
+        for p in points.windows(2) {
+            canvas.aa_line(p[0].0, p[0].1, p[1].0, p[1].1, Color::RGB(0x40, 0xd0, 0x40))?;
+        }
+        Ok(())
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        let title = match (paused, self.editing) {
+            (_, true) => "wyrmas (editing)",
+            (true, _) => "wyrmas (paused)",
+            _ => "wyrmas",
+        };
+        let _ = self.canvas.window_mut().set_title(title);
+    }
+
+    // processes pending events, returning commands for the main loop.
+    // Events addressed to brain viewer are handled here.
+    pub fn poll(&mut self) -> Vec<Command> {
+        let main_id = self.canvas.window().id();
+        let viewer_id = self.viewer.as_ref().map(|v| v.canvas.window().id());
+        let mut commands = Vec::new();
+        let events: Vec<Event> = self.events.poll_iter().collect();
+        let keys = self.events.keyboard_state();
+        let shift = keys.is_scancode_pressed(Scancode::LShift)
+            || keys.is_scancode_pressed(Scancode::RShift);
+        for event in events {
+            match event {
+                Event::Quit { .. } => commands.push(Command::Quit),
+                Event::Window {
+                    window_id,
+                    win_event: WindowEvent::Close,
+                    ..
+                }
+                | Event::KeyDown {
+                    window_id,
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => {
+                    if Some(window_id) == viewer_id {
+                        self.viewer = None;
+                    } else if window_id == main_id {
+                        commands.push(Command::Quit);
+                    }
+                }
+                Event::Window {
+                    window_id,
+                    win_event: WindowEvent::Leave,
+                    ..
+                } if Some(window_id) == viewer_id => {
+                    if let Some(v) = self.viewer.as_mut() {
+                        v.mouse = None;
+                    }
+                }
+                Event::MouseMotion {
+                    window_id, x, y, ..
+                } if Some(window_id) == viewer_id => {
+                    if let Some(v) = self.viewer.as_mut() {
+                        v.mouse = Some((x, y));
+                    }
+                }
+                // middle button drags the world around, as well as right one outside of edit mode
+                Event::MouseButtonDown {
+                    window_id,
+                    mouse_btn,
+                    x,
+                    y,
+                    ..
+                } if window_id == main_id
+                    && (mouse_btn == MouseButton::Middle
+                        || (mouse_btn == MouseButton::Right && !self.editing)) =>
+                {
+                    self.drag = Some((x, y));
+                }
+                Event::MouseMotion {
+                    window_id, x, y, ..
+                } if window_id == main_id && self.drag.is_some() => {
+                    let (px, py) = self.drag.unwrap();
+                    self.camera.pan(x - px, y - py);
+                    self.drag = Some((x, y));
+                }
+                Event::MouseWheel {
+                    window_id,
+                    y,
+                    mouse_x,
+                    mouse_y,
+                    ..
+                } if window_id == main_id => {
+                    self.camera.zoom_at(mouse_x, mouse_y, 1.25f32.powi(y));
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::T),
+                    ..
+                } => {
+                    self.overlay = self.overlay.next();
+                    println!("overlay {:?}", self.overlay);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F),
+                    ..
+                } => {
+                    let viewport = self.viewport();
+                    self.camera.fit(self.world, viewport);
+                }
+                // in edit mode left button paints selection area (or walls with shift),
+                // right button erases
+                Event::MouseButtonDown {
+                    window_id,
+                    mouse_btn,
+                    x,
+                    y,
+                    ..
+                } if window_id == main_id && self.editing => {
+                    let cell = match mouse_btn {
+                        MouseButton::Left if shift => Cell::Wall,
+                        MouseButton::Left => Cell::Selection,
+                        MouseButton::Right => Cell::Empty,
+                        _ => continue,
+                    };
+                    let p = self.camera.to_world(x, y);
+                    commands.push(Command::Paint(p, p, cell));
+                    self.last_paint = Some(p);
+                }
+                Event::MouseMotion {
+                    window_id,
+                    mousestate,
+                    x,
+                    y,
+                    ..
+                } if window_id == main_id && self.editing => {
+                    let cell = if mousestate.left() && shift {
+                        Cell::Wall
+                    } else if mousestate.left() {
+                        Cell::Selection
+                    } else if mousestate.right() {
+                        Cell::Empty
+                    } else {
+                        self.last_paint = None;
+                        continue;
+                    };
+                    let p = self.camera.to_world(x, y);
+                    commands.push(Command::Paint(self.last_paint.unwrap_or(p), p, cell));
+                    self.last_paint = Some(p);
+                }
+                Event::MouseButtonUp { window_id, .. } if window_id == main_id => {
+                    self.last_paint = None;
+                    self.drag = None;
+                }
+                Event::MouseButtonDown {
+                    window_id,
+                    mouse_btn: MouseButton::Left,
+                    x,
+                    y,
+                    ..
+                } if window_id == main_id => {
+                    let (x, y) = self.camera.to_world(x, y);
+                    commands.push(Command::Select(x, y));
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::E),
+                    ..
+                } => commands.push(Command::Edit),
+                Event::KeyDown {
+                    keycode: Some(Keycode::D),
+                    ..
+                } => commands.push(Command::Dump),
+                Event::KeyDown {
+                    keycode: Some(Keycode::V),
+                    ..
+                } => commands.push(Command::Viewer),
+                Event::KeyDown {
+                    keycode: Some(Keycode::L),
+                    ..
+                } => commands.push(Command::Lineage),
+                Event::KeyDown {
+                    keycode: Some(Keycode::H),
+                    ..
+                } => commands.push(Command::Reinject),
+                Event::KeyDown {
+                    keycode: Some(Keycode::A),
+                    ..
+                } => commands.push(Command::Animate),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Space),
+                    ..
+                } => commands.push(Command::Pause),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Period),
+                    ..
+                } => commands.push(Command::Step(Step::Tick)),
+                Event::KeyDown {
+                    keycode: Some(Keycode::G),
+                    ..
+                } => commands.push(Command::Step(Step::Generation)),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Equals | Keycode::Plus | Keycode::KpPlus),
+                    ..
+                } => commands.push(Command::Faster),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Minus | Keycode::KpMinus),
+                    ..
+                } => commands.push(Command::Slower),
+                _ => {}
+            }
+        }
+        return commands;
+    }
+
+    // switches brain viewer, if it is open, to another wyrm
+    pub fn show_in_viewer(&mut self, wyrm: usize) {
+        if let Some(v) = self.viewer.as_mut() {
+            v.wyrm = wyrm;
+        }
+    }
+
+    pub fn toggle_viewer(&mut self, sim: &Simulation, wyrm: usize) {
+        if self.viewer.take().is_some() {
+            return;
+        }
+        let Some(w) = sim.get_wyrm(wyrm) else {
+            return;
+        };
+        let layout = brainview::layout(&w.brain());
+        let (x, _) = self.canvas.window().position();
+        let mut window = self
+            .video
+            .window(
+                "wyrm brain",
+                layout.width as u32,
+                (layout.height + TOOLTIP_H) as u32,
+            )
+            .build()
+            .unwrap();
+        window.set_position(WindowPos::Positioned(x.max(0)), WindowPos::Centered);
+        self.viewer = Some(BrainViewer {
+            canvas: window.into_canvas().build().unwrap(),
+            wyrm,
+            mouse: None,
+        });
+    }
+
+    // redraws brain viewer with current neuron potentials
+    pub fn draw_viewer(&mut self, sim: &Simulation) {
+        let Some(viewer) = self.viewer.as_mut() else {
+            return;
+        };
+        let Some(w) = sim.get_wyrm(viewer.wyrm) else {
+            return;
+        };
+        let brain = w.brain();
+        let layout = brainview::layout(&brain);
+        let hovered = viewer.mouse.and_then(|p| brainview::edge_at(&layout, p));
+
+        let canvas = &mut viewer.canvas;
+        let _ = canvas
+            .window_mut()
+            .set_title(&format!("wyrm #{} brain", w.state.id));
+        canvas.set_draw_color(Color::WHITE);
+        canvas.clear();
+        if let Err(err) = brainview::draw(canvas, &brain, &layout, (0, 0), hovered) {
+            println!("error drawing brain: {err}");
+        }
+        let text = match hovered {
+            Some(i) => {
+                let e = &brain.edges[i];
+                format!(
+                    "gene #{} {:#010x}: {} -> {}, weight {:.4}",
+                    e.gene,
+                    w.state.genome[e.gene].0,
+                    brain.nodes[e.src].name,
+                    brain.nodes[e.sink].name,
+                    e.weight
+                )
+            }
+            None => String::from("hover a connection to see its gene"),
+        };
+        let _ = canvas.string(
+            8,
+            (layout.height + (TOOLTIP_H - 8) / 2) as i16,
+            &text,
+            Color::BLACK,
+        );
+        canvas.present();
+    }
+}
@@ -1,14 +1,17 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::ablation::Knockout;
+use crate::brain::{self, Brain, Layer};
 use crate::genome::{self, mix_genome, Gene};
-use crate::misc::{Dir, DIRECTIONS};
+use crate::misc::{self, Dir, DIRECTIONS};
 use crate::neuron::{Neuron, ACTIONS, ACTION_NAMES, INNER, INNER_NAME, SENSORS, SENSOR_NAMES};
 use crate::simulation::SimulationState;
-use dot_writer::{Attributes, DotWriter};
-use rand;
 
 pub struct WyrmState {
+    pub id: u64,
+    pub parents: Option<(u64, u64)>,
+    pub born: u64,
     pub dead: bool,
     pub x: i32,
     pub y: i32,
@@ -24,24 +27,36 @@ pub struct Wyrm {
     sensor_layer: Vec<Rc<RefCell<Neuron>>>,
     inner_layer: Vec<Rc<RefCell<Neuron>>>,
     action_layer: Vec<Rc<RefCell<Neuron>>>,
+    knockouts: Vec<Knockout>, // parts of the brain disabled for ablation, kept across resets
 }
 
 impl Wyrm {
-    pub fn new(x: i32, y: i32, num_inner: usize, max_dist: i32, genome: Vec<genome::Gene>) -> Self {
+    pub fn new(
+        id: u64,
+        x: i32,
+        y: i32,
+        num_inner: usize,
+        max_dist: i32,
+        genome: Vec<genome::Gene>,
+    ) -> Self {
         let mut w = Wyrm {
             state: WyrmState {
+                id: id,
+                parents: None,
+                born: 0,
                 dead: false,
                 x: x,
                 y: y,
                 age: 0,
                 max_dist: max_dist,
-                dir: DIRECTIONS[rand::random::<usize>() % DIRECTIONS.len()].clone(),
+                dir: DIRECTIONS[misc::random::<usize>() % DIRECTIONS.len()].clone(),
                 responsiveness: 1.0,
                 genome: genome,
             },
             sensor_layer: Vec::with_capacity(10),
             inner_layer: Vec::with_capacity(num_inner),
             action_layer: Vec::with_capacity(3),
+            knockouts: Vec::new(),
         };
         // earch wyrm has full set of neurons that are not necessarily wired together
         for i in 0..w.sensor_layer.capacity() {
@@ -75,7 +90,7 @@ impl Wyrm {
         self.state.dead = false;
         self.state.age = 0;
         self.state.responsiveness = 1.0;
-        self.state.dir = DIRECTIONS[rand::random::<usize>() % DIRECTIONS.len()].clone();
+        self.state.dir = DIRECTIONS[misc::random::<usize>() % DIRECTIONS.len()].clone();
         self.state.genome = genome;
         self.wire_neurons();
     }
@@ -87,15 +102,18 @@ impl Wyrm {
             .iter()
             .for_each(|n| n.borrow_mut().reset());
 
-        for g in &self.state.genome {
-            let src = match g.get_src() {
-                (true, id) => self.inner_layer[id % self.inner_layer.len()].clone(),
-                (false, id) => self.sensor_layer[id % self.sensor_layer.len()].clone(),
-            };
-            let sink = match g.get_sink() {
-                (true, id) => self.inner_layer[id % self.inner_layer.len()].clone(),
-                (false, id) => self.action_layer[id % self.action_layer.len()].clone(),
-            };
+        for layer in [Layer::Sensor, Layer::Inner, Layer::Action] {
+            for (i, n) in self.layer(layer).iter().enumerate() {
+                n.borrow_mut().disabled = self.knockouts.contains(&Knockout::Neuron(layer, i));
+            }
+        }
+
+        for (i, g) in self.state.genome.iter().enumerate() {
+            if self.knockouts.contains(&Knockout::Gene(i)) {
+                continue;
+            }
+            let (src, sink) = self.resolve(g);
+            let (src, sink) = (self.neuron(src), self.neuron(sink));
             let src = if src.borrow().name == sink.borrow().name {
                 None
             } else {
@@ -105,6 +123,36 @@ impl Wyrm {
         }
     }
 
+    pub fn knock_out(&mut self, knockouts: &[Knockout]) {
+        self.knockouts = knockouts.to_vec();
+        self.wire_neurons();
+    }
+
+    // finds which neurons are connected by the gene
+    fn resolve(&self, g: &Gene) -> ((Layer, usize), (Layer, usize)) {
+        let src = match g.get_src() {
+            (true, id) => (Layer::Inner, id % self.inner_layer.len()),
+            (false, id) => (Layer::Sensor, id % self.sensor_layer.len()),
+        };
+        let sink = match g.get_sink() {
+            (true, id) => (Layer::Inner, id % self.inner_layer.len()),
+            (false, id) => (Layer::Action, id % self.action_layer.len()),
+        };
+        return (src, sink);
+    }
+
+    fn layer(&self, layer: Layer) -> &Vec<Rc<RefCell<Neuron>>> {
+        match layer {
+            Layer::Sensor => &self.sensor_layer,
+            Layer::Inner => &self.inner_layer,
+            Layer::Action => &self.action_layer,
+        }
+    }
+
+    fn neuron(&self, (layer, id): (Layer, usize)) -> Rc<RefCell<Neuron>> {
+        self.layer(layer)[id].clone()
+    }
+
     pub fn simulation_step(&mut self, state: &mut SimulationState) {
         self.state.age += 1;
         self.sensor_layer
@@ -123,35 +171,122 @@ impl Wyrm {
     pub fn breed(&self, partner: &Self, mutation_rate: &f32) -> Vec<Gene> {
         let mut genome = mix_genome(&self.state.genome, &partner.state.genome);
         for gene in &mut genome {
-            if rand::random::<f32>() < *mutation_rate {
+            if misc::random::<f32>() < *mutation_rate {
                 gene.mutate();
             }
         }
         return genome;
     }
 
-    pub fn dump_genome(&self) -> Vec<u8> {
-        let mut dot = Vec::new();
-        {
-            let mut writer = DotWriter::from(&mut dot);
-            let mut digraph = writer.digraph();
-            for n in &self.inner_layer {
-                for (input, weight) in n.borrow().get_inputs() {
-                    digraph
-                        .edge(input, n.borrow().name.clone())
-                        .attributes()
-                        .set_label(&format!("{weight:.1}"));
+    // snapshot of wyrm's network, including neurons that are not wired
+    pub fn brain(&self) -> Brain {
+        let layers = [Layer::Sensor, Layer::Inner, Layer::Action];
+        let mut nodes = Vec::new();
+        let mut offsets = Vec::new();
+        for layer in layers {
+            offsets.push(nodes.len());
+            nodes.extend(self.layer(layer).iter().map(|n| brain::Node {
+                name: n.borrow().name.clone(),
+                layer: layer,
+                potential: n.borrow().potential,
+                fired: n.borrow().fired,
+            }));
+        }
+        let index = |(layer, id): (Layer, usize)| {
+            offsets[layers.iter().position(|l| *l == layer).unwrap()] + id
+        };
+        let edges = self
+            .state
+            .genome
+            .iter()
+            .enumerate()
+            .map(|(i, g)| {
+                let (src, sink) = self.resolve(g);
+                brain::Edge {
+                    src: index(src),
+                    sink: index(sink),
+                    weight: g.get_weight(),
+                    gene: i,
                 }
+            })
+            .collect();
+        return Brain { nodes, edges };
+    }
+
+    // human readable state of the wyrm, including decoded genome and current neuron potentials
+    #[cfg(feature = "sdl")]
+    pub fn describe(&self) -> String {
+        let brain = self.brain();
+        let mut s = format!(
+            "wyrm #{} (born in generation {}, parents {})\n  position ({}, {}), facing {}, age {}, responsiveness {:.3}{}\n  genome:\n",
+            self.state.id,
+            self.state.born,
+            match self.state.parents {
+                Some((a, b)) => format!("#{a} and #{b}"),
+                None => String::from("unknown"),
+            },
+            self.state.x,
+            self.state.y,
+            self.state.dir.name(),
+            self.state.age,
+            self.state.responsiveness,
+            if self.state.dead { ", dead" } else { "" },
+        );
+        s.push_str(&self.describe_genome());
+        for (title, layer) in [("sensors", Layer::Sensor), ("actions", Layer::Action)] {
+            s.push_str(&format!("  {title}:"));
+            for n in brain.nodes.iter().filter(|n| n.layer == layer) {
+                s.push_str(&format!(" {}={:.3}", n.name, n.potential));
             }
-            for n in &self.action_layer {
-                for (input, weight) in n.borrow().get_inputs() {
-                    digraph
-                        .edge(input, n.borrow().name.clone())
-                        .attributes()
-                        .set_label(&format!("{weight:.1}"));
+            s.push('\n');
+        }
+        return s;
+    }
+
+    pub fn inner_neurons(&self) -> usize {
+        self.inner_layer.len()
+    }
+
+    // genes of connections on some path from a sensor to an action, in genome order,
+    // the rest cannot affect behaviour
+    pub fn effective_genome(&self) -> Vec<Gene> {
+        self.brain()
+            .pruned()
+            .edges
+            .iter()
+            .map(|e| self.state.genome[e.gene].clone())
+            .collect()
+    }
+
+    // one line per gene, connections that cannot affect behaviour are marked
+    pub fn describe_genome(&self) -> String {
+        let brain = self.brain();
+        let effective = brain.effective();
+        let mut s = String::new();
+        for e in &brain.edges {
+            s.push_str(&format!(
+                "    #{:<3} {:08x}  {} -> {} ({:.3}){}\n",
+                e.gene,
+                self.state.genome[e.gene].0,
+                brain.nodes[e.src].name,
+                brain.nodes[e.sink].name,
+                e.weight,
+                if effective[e.src] && effective[e.sink] {
+                    ""
+                } else {
+                    "  unused"
                 }
-            }
+            ));
+        }
+        return s;
+    }
+
+    // dumps network in dot format, optionally omitting neurons that cannot affect behaviour
+    pub fn dump_genome(&self, prune: bool) -> Vec<u8> {
+        let brain = self.brain();
+        if prune {
+            return brain.pruned().to_dot();
         }
-        return dot;
+        return brain.to_dot();
     }
 }